@@ -0,0 +1,199 @@
+//! Arguments of the `#[function]` and `#[tool]` attributes, and of the `#[param]` attribute on
+//! tool parameters.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote_spanned;
+use syn::{
+    bracketed, meta::ParseNestedMeta, parenthesized, punctuated::Punctuated, spanned::Spanned as _,
+    Attribute, Error, Expr, Ident, LitStr, Token,
+};
+
+/// Arguments of `#[function(...)]`, or of `#[tool(...)]` on a method of a `#[tools]` impl block.
+#[derive(Default)]
+pub(crate) struct ToolArgs {
+    pub(crate) name: Option<LitStr>,
+    pub(crate) description: Option<LitStr>,
+}
+
+impl ToolArgs {
+    pub(crate) fn parse_meta(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            let name: LitStr = meta.value()?.parse()?;
+            validate_tool_name(&name)?;
+            set_once(&mut self.name, name, &meta)
+        } else if meta.path.is_ident("description") {
+            set_once(&mut self.description, meta.value()?.parse()?, &meta)
+        } else {
+            Err(meta.error("unsupported attribute, expected `name` or `description`"))
+        }
+    }
+
+    /// Parses and removes the `#[tool(...)]` attributes from `attrs`.
+    pub(crate) fn take_from(attrs: &mut Vec<Attribute>) -> syn::Result<Self> {
+        let mut args = Self::default();
+
+        for attr in take_attributes(attrs, "tool") {
+            attr.parse_nested_meta(|meta| args.parse_meta(meta))?;
+        }
+
+        Ok(args)
+    }
+}
+
+fn validate_tool_name(name: &LitStr) -> syn::Result<()> {
+    let value = name.value();
+
+    if value.is_empty()
+        || !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(Error::new_spanned(
+            name,
+            "tool name must be non-empty and only contain ASCII letters, digits, `_` and `-`",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Arguments of `#[param(...)]` on a tool parameter.
+#[derive(Default)]
+pub(crate) struct ParamArgs {
+    pub(crate) rename: Option<LitStr>,
+    pub(crate) description: Option<LitStr>,
+    pub(crate) default: Option<Expr>,
+    pub(crate) enum_values: Option<Punctuated<Expr, Token![,]>>,
+    pub(crate) range: Option<RangeArgs>,
+}
+
+pub(crate) struct RangeArgs {
+    span: proc_macro2::Span,
+    min: Option<Expr>,
+    max: Option<Expr>,
+}
+
+impl ParamArgs {
+    /// Parses the `#[param(...)]` attributes in `attrs`.
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut args = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("param")) {
+            attr.parse_nested_meta(|meta| args.parse_meta(meta))?;
+        }
+
+        Ok(args)
+    }
+
+    fn parse_meta(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("rename") {
+            set_once(&mut self.rename, meta.value()?.parse()?, &meta)
+        } else if meta.path.is_ident("description") {
+            set_once(&mut self.description, meta.value()?.parse()?, &meta)
+        } else if meta.path.is_ident("default") {
+            set_once(&mut self.default, meta.value()?.parse()?, &meta)
+        } else if meta.path.is_ident("enum_values") {
+            let input = meta.value()?;
+            let content;
+            bracketed!(content in input);
+            let values = Punctuated::parse_terminated(&content)?;
+            if values.is_empty() {
+                return Err(meta.error("`enum_values` must contain at least one value"));
+            }
+            set_once(&mut self.enum_values, values, &meta)
+        } else if meta.path.is_ident("range") {
+            let input = meta.input.fork();
+            let content;
+            parenthesized!(content in input);
+            if content.is_empty() {
+                return Err(meta.error("`range` must have a `min` or a `max`"));
+            }
+
+            let mut range = RangeArgs {
+                span: meta.path.span(),
+                min: None,
+                max: None,
+            };
+            meta.parse_nested_meta(|meta| {
+                if meta.path.is_ident("min") {
+                    set_once(&mut range.min, meta.value()?.parse()?, &meta)
+                } else if meta.path.is_ident("max") {
+                    set_once(&mut range.max, meta.value()?.parse()?, &meta)
+                } else {
+                    Err(meta.error("unsupported range bound, expected `min` or `max`"))
+                }
+            })?;
+            set_once(&mut self.range, range, &meta)
+        } else {
+            Err(meta.error(
+                "unsupported parameter attribute, expected one of `rename`, `description`, `default`, `enum_values` or `range`",
+            ))
+        }
+    }
+
+    /// The serde and schemars attributes of the generated parameter field.
+    ///
+    /// `default_fn` is the name of the function returning the default value, which must be
+    /// generated next to the parameters struct when a default is set.
+    pub(crate) fn field_attributes(&self, default_fn: &Ident) -> TokenStream2 {
+        let mut tokens = TokenStream2::new();
+
+        if let Some(rename) = &self.rename {
+            tokens.extend(quote_spanned!(rename.span() => #[serde(rename = #rename)]));
+        }
+
+        if let Some(default) = &self.default {
+            let default_fn = default_fn.to_string();
+            tokens.extend(quote_spanned!(default.span() => #[serde(default = #default_fn)]));
+        }
+
+        if let Some(values) = &self.enum_values {
+            let span = values.span();
+            let values = values.iter();
+            tokens.extend(quote_spanned!(span =>
+                #[schemars(extend("enum" = [#(#values),*]))]
+            ));
+        }
+
+        if let Some(range) = &self.range {
+            let bounds = [("min", &range.min), ("max", &range.max)]
+                .into_iter()
+                .filter_map(|(name, bound)| {
+                    let name = Ident::new(name, range.span);
+                    bound
+                        .as_ref()
+                        .map(|bound| quote_spanned!(range.span => #name = #bound))
+                });
+            tokens.extend(quote_spanned!(range.span => #[schemars(range(#(#bounds),*))]));
+        }
+
+        tokens
+    }
+}
+
+/// Removes the attributes named `name` from `attrs` and returns them.
+pub(crate) fn take_attributes(attrs: &mut Vec<Attribute>, name: &str) -> Vec<Attribute> {
+    let (taken, kept) = std::mem::take(attrs)
+        .into_iter()
+        .partition(|attr| attr.path().is_ident(name));
+    *attrs = kept;
+    taken
+}
+
+pub(crate) fn set_once<T>(
+    slot: &mut Option<T>,
+    value: T,
+    meta: &ParseNestedMeta,
+) -> syn::Result<()> {
+    if slot.is_some() {
+        let name = meta
+            .path
+            .get_ident()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        return Err(meta.error(format!("duplicate `{name}` attribute")));
+    }
+
+    *slot = Some(value);
+    Ok(())
+}
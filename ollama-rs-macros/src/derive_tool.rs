@@ -0,0 +1,251 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote_spanned;
+use syn::{
+    meta::ParseNestedMeta, parenthesized, spanned::Spanned as _, Attribute, Data, DeriveInput,
+    Error, Expr, Field, Fields, Ident, Path, Token,
+};
+
+use crate::{
+    attributes::{set_once, ToolArgs},
+    function::{build_output_conversion, build_output_imports, extract_docs},
+};
+
+pub fn derive_tool_impl(value: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(value as DeriveInput);
+
+    match expand_derive_tool(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Arguments of `#[tool(...)]` on a struct deriving `Tool`.
+#[derive(Default)]
+struct DeriveToolArgs {
+    tool: ToolArgs,
+    handler: Option<Path>,
+}
+
+impl DeriveToolArgs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut args = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("tool")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("handler") {
+                    set_once(&mut args.handler, meta.value()?.parse()?, &meta)
+                } else if meta.path.is_ident("name") || meta.path.is_ident("description") {
+                    args.tool.parse_meta(meta)
+                } else {
+                    Err(meta.error(
+                        "unsupported attribute, expected `name`, `description` or `handler`",
+                    ))
+                }
+            })?;
+        }
+
+        Ok(args)
+    }
+}
+
+fn expand_derive_tool(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let struct_name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "structs with generics are not supported, as you'd need to provide a custom description for each combination of generics",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &data.fields,
+                    "`Tool` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                struct_name,
+                "`Tool` can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let args = DeriveToolArgs::parse(&input.attrs)?;
+
+    let Some(handler) = &args.handler else {
+        return Err(Error::new_spanned(
+            struct_name,
+            "missing `#[tool(handler = ...)]` attribute",
+        ));
+    };
+
+    let description = match &args.tool.description {
+        Some(description) => description.value(),
+        None => extract_docs(&input.attrs)
+            .map(|docs| docs.description)
+            .ok_or_else(|| {
+                Error::new_spanned(
+                    struct_name,
+                    "struct must be documented, or have a `description` attribute",
+                )
+            })?,
+    };
+
+    let name = args.tool.name.as_ref().map_or_else(
+        || to_snake_case(&struct_name.to_string()),
+        |name| name.value(),
+    );
+
+    for field in fields {
+        check_field_description(field)?;
+    }
+
+    let span = input.span();
+    let visibility = &input.vis;
+    let tool_struct_name = Ident::new(&format!("{struct_name}Tool"), struct_name.span());
+    let tool_struct_doc = format!("The tool taking [`{struct_name}`] as parameters.");
+    let imports = build_output_imports(span);
+    let conversion = build_output_conversion(span, true);
+
+    Ok(quote_spanned!(span =>
+        #[doc = #tool_struct_doc]
+        #visibility struct #tool_struct_name;
+
+        impl ::ollama_rs::generation::tools::Tool for #tool_struct_name {
+            type Params = #struct_name;
+
+            #[inline]
+            fn name() -> &'static str {
+                #name
+            }
+
+            #[inline]
+            fn description() -> &'static str {
+                #description
+            }
+
+            async fn call(&mut self, parameters: Self::Params) -> ::std::result::Result<
+                ::std::string::String,
+                ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
+            > {
+                #imports
+                #[allow(unused_imports)]
+                use ::ollama_rs::generation::tools::macro_support::{
+                    PlainOutputKind as _, ResultOutputKind as _,
+                };
+
+                let output = #handler(parameters).await;
+                // Outputs that aren't a `Result` are converted into one
+                let output = (&output).output_kind().into_result(output);
+                #conversion
+            }
+        }
+    ))
+}
+
+/// Fields must be described to the model, either with a doc comment or with
+/// `#[schemars(description = "...")]`, unless they are skipped or flattened.
+fn check_field_description(field: &Field) -> syn::Result<()> {
+    let has_docs = field.attrs.iter().any(|attr| {
+        attr.path().is_ident("doc")
+            && matches!(
+                &attr.meta,
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    value: Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }),
+                    ..
+                }) if !doc.value().trim().is_empty()
+            )
+    });
+    if has_docs {
+        return Ok(());
+    }
+
+    let mut described = false;
+    for attr in &field.attrs {
+        let is_serde = attr.path().is_ident("serde");
+        if !is_serde && !attr.path().is_ident("schemars") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            let is_skipped = ["skip", "skip_deserializing", "flatten"]
+                .iter()
+                .any(|name| meta.path.is_ident(name));
+            if is_skipped || (!is_serde && meta.path.is_ident("description")) {
+                described = true;
+            }
+            skip_meta_value(&meta)
+        })?;
+    }
+
+    if described {
+        return Ok(());
+    }
+
+    let name = field
+        .ident
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    Err(Error::new_spanned(
+        field,
+        format!(
+            "field `{name}` must have a description, either as a doc comment or with `#[schemars(description = \"...\")]`"
+        ),
+    ))
+}
+
+/// Skips the value of a nested meta item that isn't otherwise parsed.
+fn skip_meta_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let _content;
+        parenthesized!(_content in meta.input);
+    }
+
+    Ok(())
+}
+
+/// Converts a struct name into snake case, keeping acronyms together: `HTTPGet` becomes
+/// `http_get`.
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake_case = String::with_capacity(name.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lowercase = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            // A word starts after a lowercase letter or a digit, or at the last capital of an
+            // acronym followed by a lowercase letter
+            if previous != '_' && (!previous.is_uppercase() || next_is_lowercase) {
+                snake_case.push('_');
+            }
+        }
+        snake_case.extend(c.to_lowercase());
+    }
+
+    snake_case
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_snake_case;
+
+    #[test]
+    fn acronyms_are_kept_together() {
+        assert_eq!(to_snake_case("GetWeather"), "get_weather");
+        assert_eq!(to_snake_case("HTTPGet"), "http_get");
+        assert_eq!(to_snake_case("GetHTTP"), "get_http");
+        assert_eq!(to_snake_case("ParseJSONValue"), "parse_json_value");
+        assert_eq!(to_snake_case("Sha256Sum"), "sha256_sum");
+    }
+}
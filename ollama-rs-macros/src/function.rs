@@ -3,21 +3,21 @@
 use std::collections::HashMap;
 
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{quote_spanned, ToTokens};
 use syn::{
-    spanned::Spanned as _, Error, Expr, ExprLit, FnArg, Ident, ItemFn, Lit, Meta, MetaNameValue,
-    Pat, Type,
+    spanned::Spanned as _, Attribute, Error, Expr, ExprLit, FnArg, Ident, ItemFn, Lit, Meta,
+    MetaNameValue, Pat, ReturnType, Signature, Type, TypePath, Visibility,
 };
 
-pub fn function_impl(_attr: TokenStream, value: TokenStream) -> TokenStream {
-    let input = syn::parse_macro_input!(value as ItemFn);
+use crate::attributes::{take_attributes, ParamArgs, ToolArgs};
 
-    if input.sig.asyncness.is_none() {
-        return Error::new_spanned(input.sig.fn_token, "function must be async")
-            .to_compile_error()
-            .into();
-    }
+pub fn function_impl(attr: TokenStream, value: TokenStream) -> TokenStream {
+    let mut args = ToolArgs::default();
+    let args_parser = syn::meta::parser(|meta| args.parse_meta(meta));
+    syn::parse_macro_input!(attr with args_parser);
+
+    let input = syn::parse_macro_input!(value as ItemFn);
 
     if !input.sig.generics.params.is_empty() {
         return Error::new_spanned(
@@ -28,23 +28,29 @@ pub fn function_impl(_attr: TokenStream, value: TokenStream) -> TokenStream {
         .into();
     }
 
-    let Some(docs) = extract_docs(&input) else {
-        return Error::new_spanned(input.sig.fn_token, "function must be documented")
-            .to_compile_error()
-            .into();
+    let Some(docs) = tool_docs(&input.attrs, &args) else {
+        return Error::new_spanned(
+            input.sig.fn_token,
+            "function must be documented, or have a `description` attribute",
+        )
+        .to_compile_error()
+        .into();
     };
 
     let vis = &input.vis;
     let function_name = &input.sig.ident;
     let function_module_name = Ident::new(&format!("__{}_data", input.sig.ident), input.span());
 
-    let params_struct = match build_params_struct(&input, &docs) {
+    let params_struct = match build_params_struct(&input.sig, input.sig.inputs.iter(), &docs) {
         Ok(params_struct) => params_struct,
         Err(err) => return err.to_compile_error().into(),
     };
 
+    let tool_name = tool_name(&input.sig, &args);
+
     let tool_impl = build_tool_impl(
         &input,
+        &tool_name,
         &docs,
         &params_struct,
         function_name,
@@ -73,19 +79,59 @@ pub fn function_impl(_attr: TokenStream, value: TokenStream) -> TokenStream {
     .into()
 }
 
+/// The name of the tool: the `name` attribute if set, otherwise the function name.
+pub(crate) fn tool_name(sig: &Signature, args: &ToolArgs) -> String {
+    args.name
+        .as_ref()
+        .map_or_else(|| sig.ident.to_string(), |name| name.value())
+}
+
+/// The docs of the tool: the `description` attribute, if set, takes precedence over the
+/// description from the doc comment.
+pub(crate) fn tool_docs(attrs: &[Attribute], args: &ToolArgs) -> Option<FunctionDocs> {
+    let docs = extract_docs(attrs);
+
+    match &args.description {
+        Some(description) => Some(FunctionDocs {
+            description: description.value(),
+            parameter_docs: docs.map(|docs| docs.parameter_docs).unwrap_or_default(),
+        }),
+        None => docs,
+    }
+}
+
 fn build_tool_impl(
     input: &ItemFn,
+    function_name_str: &str,
     docs: &FunctionDocs,
     params_struct: &ParamsStruct,
     function_name: &Ident,
     function_module_name: &Ident,
 ) -> TokenStream2 {
-    let function_name_str = function_name.to_string();
-    let function_body = &input.block;
     let function_description = &docs.description;
 
     let function_params_struct_name = &params_struct.name;
-    let function_params_struct_field_names = params_struct.fields.iter().map(|field| &field.name);
+    let function_params_struct_field_names = params_struct
+        .fields
+        .iter()
+        .map(|field| &field.name)
+        .collect::<Vec<_>>();
+
+    // The function is kept as is inside `call`, so that its body is checked against its own
+    // signature, and its output is then converted for the model
+    let mut function = input.clone();
+    function.vis = Visibility::Inherited;
+    function.attrs.retain(|attr| !attr.path().is_ident("doc"));
+    for arg in &mut function.sig.inputs {
+        if let FnArg::Typed(pat_type) = arg {
+            take_attributes(&mut pat_type.attrs, "param");
+        }
+    }
+
+    let call_body = build_call_body(
+        &input.sig,
+        quote_spanned!(input.span() => #function_name(#(#function_params_struct_field_names),*)),
+    );
 
     quote_spanned!(input.span() =>
         impl ::ollama_rs::generation::tools::Tool for #function_name {
@@ -105,20 +151,99 @@ fn build_tool_impl(
                 ::std::string::String,
                 ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
             > {
-                #function_body
+                #function
+
+                #call_body
             }
         }
     )
 }
 
-fn build_params_struct(input: &ItemFn, docs: &FunctionDocs) -> syn::Result<ParamsStruct> {
-    let name = Ident::new(
-        &format!("__{}__Params", input.sig.ident),
-        input.sig.ident.span(),
-    );
-    let span = input.span();
-    let fields = prepare_params_struct_fields(input, docs)?;
+/// Builds the body of `Tool::call`, which runs `invocation` and converts its output.
+///
+/// Async functions are awaited, while other functions are run on a blocking thread. Functions
+/// returning a `Result` have their `Ok` value converted with `into_tool_output`, and their `Err`
+/// value with `into_tool_error`; other functions have their whole output converted.
+pub(crate) fn build_call_body(sig: &Signature, invocation: TokenStream2) -> TokenStream2 {
+    let span = sig.span();
+
+    let output = if sig.asyncness.is_some() {
+        quote_spanned!(span => let output = #invocation.await;)
+    } else {
+        quote_spanned!(span =>
+            let output = ::ollama_rs::generation::tools::macro_support::spawn_blocking(
+                move || #invocation,
+            ).await?;
+        )
+    };
+
+    let imports = build_output_imports(span);
+    let conversion = build_output_conversion(span, returns_result(&sig.output));
+
+    quote_spanned!(span =>
+        #imports
+
+        #output
+        #conversion
+    )
+}
+
+/// Imports the conversions used by [`build_output_conversion`].
+pub(crate) fn build_output_imports(span: Span) -> TokenStream2 {
+    quote_spanned!(span =>
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+    )
+}
+
+/// Converts the `output` variable into the result of `Tool::call`.
+pub(crate) fn build_output_conversion(span: Span, is_result: bool) -> TokenStream2 {
+    if is_result {
+        quote_spanned!(span =>
+            match output {
+                ::std::result::Result::Ok(output) => Output(output).into_tool_output(),
+                ::std::result::Result::Err(error) => {
+                    ::std::result::Result::Err(ToolError(error).into_tool_error())
+                }
+            }
+        )
+    } else {
+        quote_spanned!(span => Output(output).into_tool_output())
+    }
+}
+
+/// Whether the function returns a `Result`, judging by the name of the return type.
+///
+/// Macros can't resolve types, so any path ending with `Result`, such as `io::Result<T>` or
+/// `anyhow::Result<T>`, is a result, and aliases with other names are plain outputs.
+fn returns_result(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(TypePath { qself: None, path }) => path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+pub(crate) fn build_params_struct<'a>(
+    sig: &Signature,
+    inputs: impl Iterator<Item = &'a FnArg>,
+    docs: &FunctionDocs,
+) -> syn::Result<ParamsStruct> {
+    let name = Ident::new(&format!("__{}__Params", sig.ident), sig.ident.span());
+    let span = sig.span();
+    let fields = prepare_params_struct_fields(sig, inputs, docs)?;
+    let default_fns = fields.iter().filter_map(ParamsField::default_fn);
     let tokens = quote_spanned!(span =>
+        #(#default_fns)*
+
         #[doc(hidden)]
         #[allow(non_camel_case_types, missing_docs)]
         #[derive(serde::Deserialize, schemars::JsonSchema)]
@@ -135,16 +260,35 @@ fn build_params_struct(input: &ItemFn, docs: &FunctionDocs) -> syn::Result<Param
     })
 }
 
-struct ParamsStruct {
-    name: Ident,
-    fields: Vec<ParamsField>,
-    tokens: TokenStream2,
+pub(crate) struct ParamsStruct {
+    pub(crate) name: Ident,
+    pub(crate) fields: Vec<ParamsField>,
+    pub(crate) tokens: TokenStream2,
 }
 
-struct ParamsField {
-    name: Ident,
+pub(crate) struct ParamsField {
+    pub(crate) name: Ident,
     ty: Type,
     docs: String,
+    args: ParamArgs,
+    default_fn_name: Ident,
+}
+
+impl ParamsField {
+    /// The function returning the default value of this parameter, if it has one.
+    fn default_fn(&self) -> Option<TokenStream2> {
+        let default = self.args.default.as_ref()?;
+        let name = &self.default_fn_name;
+        let ty = &self.ty;
+
+        Some(quote_spanned!(default.span() =>
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            fn #name() -> #ty {
+                #default
+            }
+        ))
+    }
 }
 
 impl ToTokens for ParamsField {
@@ -152,22 +296,22 @@ impl ToTokens for ParamsField {
         let name = &self.name;
         let ty = &self.ty;
         let docs = &self.docs;
+        let attributes = self.args.field_attributes(&self.default_fn_name);
 
         tokens.extend(quote_spanned!(self.name.span() =>
             #[doc = #docs]
+            #attributes
             pub #name: #ty,
         ));
     }
 }
 
-fn prepare_params_struct_fields(
-    input: &ItemFn,
+fn prepare_params_struct_fields<'a>(
+    sig: &Signature,
+    inputs: impl Iterator<Item = &'a FnArg>,
     docs: &FunctionDocs,
 ) -> syn::Result<Vec<ParamsField>> {
-    input
-        .sig
-        .inputs
-        .iter()
+    inputs
         .map(|arg| {
             let pat_type = match arg {
                 FnArg::Receiver(_) => {
@@ -182,25 +326,34 @@ fn prepare_params_struct_fields(
                     "only named arguments are allowed, e.g. `a: i32`",
                 ));
             };
+            let args = ParamArgs::parse(&pat_type.attrs)?;
             let name_str = name.ident.to_string();
-            let docs = docs
-                .parameter_docs
-                .get(&name_str)
-                .cloned()
-                .unwrap_or(name_str);
+            let docs = match &args.description {
+                Some(description) => description.value(),
+                None => docs
+                    .parameter_docs
+                    .get(&name_str)
+                    .cloned()
+                    .unwrap_or_else(|| name_str.clone()),
+            };
+            let default_fn_name = Ident::new(
+                &format!("__{}__{name_str}__default", sig.ident),
+                name.ident.span(),
+            );
 
             Ok(ParamsField {
                 name: name.ident.clone(),
                 ty: *pat_type.ty.clone(),
                 docs,
+                args,
+                default_fn_name,
             })
         })
         .collect()
 }
 
-fn extract_docs(input: &ItemFn) -> Option<FunctionDocs> {
-    let docs = input
-        .attrs
+pub(crate) fn extract_docs(attrs: &[Attribute]) -> Option<FunctionDocs> {
+    let docs = attrs
         .iter()
         .filter_map(|attr| {
             if !attr.path().is_ident("doc") {
@@ -261,7 +414,7 @@ fn extract_docs(input: &ItemFn) -> Option<FunctionDocs> {
 }
 
 #[derive(Debug, Clone)]
-struct FunctionDocs {
-    description: String,
-    parameter_docs: HashMap<String, String>,
+pub(crate) struct FunctionDocs {
+    pub(crate) description: String,
+    pub(crate) parameter_docs: HashMap<String, String>,
 }
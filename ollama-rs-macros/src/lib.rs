@@ -1,8 +1,21 @@
 use proc_macro::TokenStream;
 
+mod attributes;
+mod derive_tool;
 mod function;
+mod tools;
 
 #[proc_macro_attribute]
 pub fn function(attr: TokenStream, value: TokenStream) -> TokenStream {
     function::function_impl(attr, value)
 }
+
+#[proc_macro_attribute]
+pub fn tools(attr: TokenStream, value: TokenStream) -> TokenStream {
+    tools::tools_impl(attr, value)
+}
+
+#[proc_macro_derive(Tool, attributes(tool))]
+pub fn derive_tool(value: TokenStream) -> TokenStream {
+    derive_tool::derive_tool_impl(value)
+}
@@ -0,0 +1,231 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote_spanned;
+use syn::{
+    spanned::Spanned as _, Error, FnArg, Ident, ImplItem, ImplItemFn, ItemImpl, Type, TypePath,
+};
+
+use crate::{
+    attributes::{take_attributes, ToolArgs},
+    function::{
+        build_call_body, build_params_struct, tool_docs, tool_name, FunctionDocs, ParamsStruct,
+    },
+};
+
+pub fn tools_impl(_attr: TokenStream, value: TokenStream) -> TokenStream {
+    let mut input = syn::parse_macro_input!(value as ItemImpl);
+
+    match expand_tools(&mut input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_tools(input: &mut ItemImpl) -> syn::Result<TokenStream2> {
+    if let Some((_, trait_path, _)) = &input.trait_ {
+        return Err(Error::new_spanned(
+            trait_path,
+            "#[tools] must be used on an inherent impl block",
+        ));
+    }
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "impl blocks with generics are not supported, as you'd need to provide a custom description for each combination of generics",
+        ));
+    }
+
+    let self_ty = &input.self_ty;
+    let type_name = match &**self_ty {
+        Type::Path(TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .filter(|segment| segment.arguments.is_empty())
+            .map(|segment| segment.ident.clone()),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        Error::new_spanned(
+            self_ty,
+            "#[tools] only supports impl blocks for named types without generics",
+        )
+    })?;
+
+    let tools_module_name = Ident::new(&format!("__{type_name}_tools"), type_name.span());
+
+    let mut methods = Vec::new();
+    for item in &mut input.items {
+        let ImplItem::Fn(method) = item else {
+            continue;
+        };
+
+        // Methods marked with `#[tool]` must be tools, rather than being silently skipped
+        let marked = method.attrs.iter().any(|attr| attr.path().is_ident("tool"));
+
+        // The helper attributes must not be emitted along with the impl block
+        let args = ToolArgs::take_from(&mut method.attrs)?;
+        let original = method.clone();
+        for arg in &mut method.sig.inputs {
+            if let FnArg::Typed(pat_type) = arg {
+                take_attributes(&mut pat_type.attrs, "param");
+            }
+        }
+
+        if !is_tool_method(&original) {
+            if marked {
+                return Err(Error::new_spanned(
+                    &original.sig,
+                    "#[tool] methods must be `async fn(&self, ...)` without generics",
+                ));
+            }
+            continue;
+        }
+        let Some(docs) = tool_docs(&original.attrs, &args) else {
+            if marked {
+                return Err(Error::new_spanned(
+                    &original.sig,
+                    "#[tool] methods need a doc comment or a `description`",
+                ));
+            }
+            continue;
+        };
+
+        let inputs = original
+            .sig
+            .inputs
+            .iter()
+            .filter(|arg| !matches!(arg, FnArg::Receiver(_)));
+        let params_struct = build_params_struct(&original.sig, inputs, &docs)?;
+
+        methods.push(ToolMethod {
+            name: tool_name(&original.sig, &args),
+            method: original,
+            docs,
+            params_struct,
+        });
+    }
+
+    if methods.is_empty() {
+        return Err(Error::new_spanned(
+            self_ty,
+            "#[tools] requires at least one documented `async fn(&self, ...)`",
+        ));
+    }
+
+    let tool_structs = methods.iter().map(|tool| {
+        let function_name = &tool.method.sig.ident;
+        let params_struct_definition = &tool.params_struct.tokens;
+
+        quote_spanned!(tool.method.span() =>
+            #params_struct_definition
+
+            #[allow(non_camel_case_types)]
+            pub struct #function_name(pub(super) ::std::sync::Arc<#self_ty>);
+        )
+    });
+
+    let tool_impls = methods
+        .iter()
+        .map(|tool| build_tool_impl(tool, &tools_module_name));
+
+    let tool_registrations = methods.iter().map(|tool| {
+        let function_name = &tool.method.sig.ident;
+
+        quote_spanned!(tool.method.span() =>
+            let coordinator = coordinator.add_tool(
+                #tools_module_name::#function_name(::std::sync::Arc::clone(&self)),
+            );
+        )
+    });
+
+    Ok(quote_spanned!(input.span() =>
+        #input
+
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        mod #tools_module_name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            use ollama_rs::re_exports::schemars;
+            use ollama_rs::re_exports::serde;
+
+            #(#tool_structs)*
+        }
+
+        #(#tool_impls)*
+
+        impl ::ollama_rs::generation::tools::ToolGroup for #self_ty {
+            fn add_tools<C: ::ollama_rs::history::AsyncChatHistory>(
+                self: ::std::sync::Arc<Self>,
+                coordinator: ::ollama_rs::coordinator::Coordinator<C>,
+            ) -> ::ollama_rs::coordinator::Coordinator<C> {
+                #(#tool_registrations)*
+                coordinator
+            }
+        }
+    ))
+}
+
+struct ToolMethod {
+    name: String,
+    method: ImplItemFn,
+    docs: FunctionDocs,
+    params_struct: ParamsStruct,
+}
+
+/// Only documented `async fn(&self, ...)` methods become tools, so that the impl block can
+/// also contain constructors and helpers.
+fn is_tool_method(method: &ImplItemFn) -> bool {
+    let takes_shared_self = matches!(
+        method.sig.inputs.first(),
+        Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() && receiver.mutability.is_none()
+    );
+
+    method.sig.asyncness.is_some() && method.sig.generics.params.is_empty() && takes_shared_self
+}
+
+fn build_tool_impl(tool: &ToolMethod, tools_module_name: &Ident) -> TokenStream2 {
+    let function_name = &tool.method.sig.ident;
+    let function_name_str = &tool.name;
+    let function_description = &tool.docs.description;
+
+    let function_params_struct_name = &tool.params_struct.name;
+    let function_params_struct_field_names = tool
+        .params_struct
+        .fields
+        .iter()
+        .map(|field| &field.name)
+        .collect::<Vec<_>>();
+
+    let call_body = build_call_body(
+        &tool.method.sig,
+        quote_spanned!(tool.method.span() =>
+            self.0.#function_name(#(#function_params_struct_field_names),*)
+        ),
+    );
+
+    quote_spanned!(tool.method.span() =>
+        impl ::ollama_rs::generation::tools::Tool for #tools_module_name::#function_name {
+            type Params = #tools_module_name::#function_params_struct_name;
+
+            #[inline]
+            fn name() -> &'static str {
+                #function_name_str
+            }
+
+            #[inline]
+            fn description() -> &'static str {
+                #function_description
+            }
+
+            async fn call(&mut self, Self::Params { #(#function_params_struct_field_names),* }: Self::Params) -> ::std::result::Result<
+                ::std::string::String,
+                ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
+            > {
+                #call_body
+            }
+        }
+    )
+}
@@ -0,0 +1,57 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+/// Get the weather for a given city
+#[tool(handler = get_weather)]
+struct GetWeather {
+    /// The city to get the weather for
+    city: String,
+    #[schemars(description = "The number of days to forecast")]
+    days: u8,
+}
+///The tool taking [`GetWeather`] as parameters.
+struct GetWeatherTool;
+impl ::ollama_rs::generation::tools::Tool for GetWeatherTool {
+    type Params = GetWeather;
+    #[inline]
+    fn name() -> &'static str {
+        "get_weather"
+    }
+    #[inline]
+    fn description() -> &'static str {
+        "Get the weather for a given city"
+    }
+    async fn call(
+        &mut self,
+        parameters: Self::Params,
+    ) -> ::std::result::Result<
+        ::std::string::String,
+        ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
+    > {
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            PlainOutputKind as _, ResultOutputKind as _,
+        };
+        let output = get_weather(parameters).await;
+        let output = (&output).output_kind().into_result(output);
+        match output {
+            ::std::result::Result::Ok(output) => Output(output).into_tool_output(),
+            ::std::result::Result::Err(error) => {
+                ::std::result::Result::Err(ToolError(error).into_tool_error())
+            }
+        }
+    }
+}
+async fn get_weather(params: GetWeather) -> Result<String, std::fmt::Error> {
+    Ok(
+        ::alloc::__export::must_use({
+            ::alloc::fmt::format(
+                format_args!("{0}: sunny for {1} days", params.city, params.days),
+            )
+        }),
+    )
+}
@@ -0,0 +1,16 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+/// Get the weather for a given city
+#[derive(Tool)]
+#[tool(handler = get_weather)]
+struct GetWeather {
+    /// The city to get the weather for
+    city: String,
+    #[schemars(description = "The number of days to forecast")]
+    days: u8,
+}
+
+async fn get_weather(params: GetWeather) -> Result<String, std::fmt::Error> {
+    Ok(format!("{}: sunny for {} days", params.city, params.days))
+}
@@ -0,0 +1,73 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+#[doc(hidden)]
+mod __get_weather_data {
+    #[allow(unused_imports)]
+    use super::*;
+    use ollama_rs::re_exports::schemars;
+    use ollama_rs::re_exports::serde;
+    #[doc(hidden)]
+    #[allow(non_snake_case)]
+    fn __get_weather__unit__default() -> String {
+        "celsius".to_string()
+    }
+    #[doc(hidden)]
+    #[allow(non_camel_case_types, missing_docs)]
+    #[serde(crate = "ollama_rs::re_exports::serde")]
+    pub struct __get_weather__Params {
+        ///City to get the weather for
+        pub city: String,
+        ///unit
+        #[serde(rename = "temperatureUnit")]
+        #[serde(default = "__get_weather__unit__default")]
+        #[schemars(extend("enum" = ["celsius", "fahrenheit"]))]
+        pub unit: String,
+        ///Number of days to forecast
+        #[schemars(range(min = 1, max = 7))]
+        pub days: u8,
+    }
+}
+#[allow(non_camel_case_types)]
+struct get_weather;
+impl ::ollama_rs::generation::tools::Tool for get_weather {
+    type Params = __get_weather_data::__get_weather__Params;
+    #[inline]
+    fn name() -> &'static str {
+        "weather"
+    }
+    #[inline]
+    fn description() -> &'static str {
+        "Get the weather for a city"
+    }
+    async fn call(
+        &mut self,
+        Self::Params { city, unit, days }: Self::Params,
+    ) -> ::std::result::Result<
+        ::std::string::String,
+        ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
+    > {
+        async fn get_weather(
+            city: String,
+            unit: String,
+            days: u8,
+        ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(
+                ::alloc::__export::must_use({
+                    ::alloc::fmt::format(format_args!("{0} {1} {2}", city, unit, days))
+                }),
+            )
+        }
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        let output = get_weather(city, unit, days).await;
+        match output {
+            ::std::result::Result::Ok(output) => Output(output).into_tool_output(),
+            ::std::result::Result::Err(error) => {
+                ::std::result::Result::Err(ToolError(error).into_tool_error())
+            }
+        }
+    }
+}
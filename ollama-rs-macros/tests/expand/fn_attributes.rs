@@ -0,0 +1,15 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+/// Get the weather
+///
+/// * city - City to get the weather for
+#[function(name = "weather", description = "Get the weather for a city")]
+async fn get_weather(
+    city: String,
+    #[param(rename = "temperatureUnit", default = "celsius".to_string(), enum_values = ["celsius", "fahrenheit"])]
+    unit: String,
+    #[param(range(min = 1, max = 7), description = "Number of days to forecast")] days: u8,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(format!("{} {} {}", city, unit, days))
+}
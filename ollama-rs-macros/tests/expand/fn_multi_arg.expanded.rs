@@ -35,13 +35,28 @@ impl ::ollama_rs::generation::tools::Tool for hello_world {
         ::std::string::String,
         ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
     > {
-        {
+        async fn hello_world(
+            greeting: String,
+            name: String,
+        ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
             Ok(
                 ::alloc::__export::must_use({
                     ::alloc::fmt::format(format_args!("{0} {1}", greeting, name))
                 }),
             )
         }
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        let output = hello_world(greeting, name).await;
+        match output {
+            ::std::result::Result::Ok(output) => Output(output).into_tool_output(),
+            ::std::result::Result::Err(error) => {
+                ::std::result::Result::Err(ToolError(error).into_tool_error())
+            }
+        }
     }
 }
 #[doc(hidden)]
@@ -81,7 +96,11 @@ impl ::ollama_rs::generation::tools::Tool for dummy {
         ::std::string::String,
         ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
     > {
-        {
+        async fn dummy(
+            one: String,
+            two: i32,
+            three: bool,
+        ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
             Ok(
                 ::alloc::__export::must_use({
                     ::alloc::fmt::format(
@@ -90,5 +109,17 @@ impl ::ollama_rs::generation::tools::Tool for dummy {
                 }),
             )
         }
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        let output = dummy(one, two, three).await;
+        match output {
+            ::std::result::Result::Ok(output) => Output(output).into_tool_output(),
+            ::std::result::Result::Err(error) => {
+                ::std::result::Result::Err(ToolError(error).into_tool_error())
+            }
+        }
     }
 }
@@ -30,6 +30,23 @@ impl ::ollama_rs::generation::tools::Tool for hello_world {
         ::std::string::String,
         ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
     > {
-        { Ok("Hello".to_string()) }
+        async fn hello_world() -> Result<
+            String,
+            Box<dyn std::error::Error + Send + Sync>,
+        > {
+            Ok("Hello".to_string())
+        }
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        let output = hello_world().await;
+        match output {
+            ::std::result::Result::Ok(output) => Output(output).into_tool_output(),
+            ::std::result::Result::Err(error) => {
+                ::std::result::Result::Err(ToolError(error).into_tool_error())
+            }
+        }
     }
 }
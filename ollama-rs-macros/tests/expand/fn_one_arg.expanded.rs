@@ -33,12 +33,26 @@ impl ::ollama_rs::generation::tools::Tool for hello_world {
         ::std::string::String,
         ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
     > {
-        {
+        async fn hello_world(
+            name: String,
+        ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
             Ok(
                 ::alloc::__export::must_use({
                     ::alloc::fmt::format(format_args!("Hello {0}", name))
                 }),
             )
         }
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        let output = hello_world(name).await;
+        match output {
+            ::std::result::Result::Ok(output) => Output(output).into_tool_output(),
+            ::std::result::Result::Err(error) => {
+                ::std::result::Result::Err(ToolError(error).into_tool_error())
+            }
+        }
     }
 }
@@ -0,0 +1,58 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+struct NotFound;
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("not found")
+    }
+}
+#[doc(hidden)]
+mod __get_population_data {
+    #[allow(unused_imports)]
+    use super::*;
+    use ollama_rs::re_exports::schemars;
+    use ollama_rs::re_exports::serde;
+    #[doc(hidden)]
+    #[allow(non_camel_case_types, missing_docs)]
+    #[serde(crate = "ollama_rs::re_exports::serde")]
+    pub struct __get_population__Params {
+        ///The city to look up
+        pub city: String,
+    }
+}
+#[allow(non_camel_case_types)]
+struct get_population;
+impl ::ollama_rs::generation::tools::Tool for get_population {
+    type Params = __get_population_data::__get_population__Params;
+    #[inline]
+    fn name() -> &'static str {
+        "get_population"
+    }
+    #[inline]
+    fn description() -> &'static str {
+        "Look up the population of a city"
+    }
+    async fn call(
+        &mut self,
+        Self::Params { city }: Self::Params,
+    ) -> ::std::result::Result<
+        ::std::string::String,
+        ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
+    > {
+        async fn get_population(city: String) -> Result<u64, NotFound> {
+            if city == "Paris" { Ok(2102650) } else { Err(NotFound) }
+        }
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        let output = get_population(city).await;
+        match output {
+            ::std::result::Result::Ok(output) => Output(output).into_tool_output(),
+            ::std::result::Result::Err(error) => {
+                ::std::result::Result::Err(ToolError(error).into_tool_error())
+            }
+        }
+    }
+}
@@ -0,0 +1,18 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+struct NotFound;
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("not found")
+    }
+}
+
+/// Look up the population of a city
+///
+/// * city - The city to look up
+#[function]
+async fn get_population(city: String) -> Result<u64, NotFound> {
+    if city == "Paris" { Ok(2102650) } else { Err(NotFound) }
+}
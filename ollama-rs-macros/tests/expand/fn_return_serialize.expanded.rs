@@ -0,0 +1,56 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+#[doc(hidden)]
+mod __list_cities_data {
+    #[allow(unused_imports)]
+    use super::*;
+    use ollama_rs::re_exports::schemars;
+    use ollama_rs::re_exports::serde;
+    #[doc(hidden)]
+    #[allow(non_camel_case_types, missing_docs)]
+    #[serde(crate = "ollama_rs::re_exports::serde")]
+    pub struct __list_cities__Params {
+        ///The country to list the cities of
+        pub country: String,
+    }
+}
+#[allow(non_camel_case_types)]
+struct list_cities;
+impl ::ollama_rs::generation::tools::Tool for list_cities {
+    type Params = __list_cities_data::__list_cities__Params;
+    #[inline]
+    fn name() -> &'static str {
+        "list_cities"
+    }
+    #[inline]
+    fn description() -> &'static str {
+        "List the cities in a country"
+    }
+    async fn call(
+        &mut self,
+        Self::Params { country }: Self::Params,
+    ) -> ::std::result::Result<
+        ::std::string::String,
+        ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
+    > {
+        async fn list_cities(country: String) -> Vec<String> {
+            ::alloc::boxed::box_assume_init_into_vec_unsafe(
+                ::alloc::intrinsics::write_box_via_move(
+                    ::alloc::boxed::Box::new_uninit(),
+                    [
+                        ::alloc::__export::must_use({
+                            ::alloc::fmt::format(format_args!("Capital of {0}", country))
+                        }),
+                    ],
+                ),
+            )
+        }
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        let output = list_cities(country).await;
+        Output(output).into_tool_output()
+    }
+}
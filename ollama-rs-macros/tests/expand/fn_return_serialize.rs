@@ -0,0 +1,10 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+/// List the cities in a country
+///
+/// * country - The country to list the cities of
+#[function]
+async fn list_cities(country: String) -> Vec<String> {
+    vec![format!("Capital of {}", country)]
+}
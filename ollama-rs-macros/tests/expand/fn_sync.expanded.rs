@@ -0,0 +1,49 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+#[doc(hidden)]
+mod __count_words_data {
+    #[allow(unused_imports)]
+    use super::*;
+    use ollama_rs::re_exports::schemars;
+    use ollama_rs::re_exports::serde;
+    #[doc(hidden)]
+    #[allow(non_camel_case_types, missing_docs)]
+    #[serde(crate = "ollama_rs::re_exports::serde")]
+    pub struct __count_words__Params {
+        ///The text to count the words of
+        pub text: String,
+    }
+}
+#[allow(non_camel_case_types)]
+struct count_words;
+impl ::ollama_rs::generation::tools::Tool for count_words {
+    type Params = __count_words_data::__count_words__Params;
+    #[inline]
+    fn name() -> &'static str {
+        "count_words"
+    }
+    #[inline]
+    fn description() -> &'static str {
+        "Count the words of a text"
+    }
+    async fn call(
+        &mut self,
+        Self::Params { text }: Self::Params,
+    ) -> ::std::result::Result<
+        ::std::string::String,
+        ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
+    > {
+        fn count_words(text: String) -> usize {
+            text.split_whitespace().count()
+        }
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        let output = ::ollama_rs::generation::tools::macro_support::spawn_blocking(move ||
+            count_words(text))
+            .await?;
+        Output(output).into_tool_output()
+    }
+}
@@ -0,0 +1,10 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+/// Count the words of a text
+///
+/// * text - The text to count the words of
+#[function]
+fn count_words(text: String) -> usize {
+    text.split_whitespace().count()
+}
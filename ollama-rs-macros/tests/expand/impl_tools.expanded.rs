@@ -0,0 +1,81 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+struct Greeter {
+    greeting: String,
+}
+impl Greeter {
+    fn new(greeting: String) -> Self {
+        Self { greeting }
+    }
+    /// Say hello
+    ///
+    /// * name - Whom to say hello to
+    async fn hello_world(
+        &self,
+        name: String,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(
+            ::alloc::__export::must_use({
+                ::alloc::fmt::format(format_args!("{0} {1}", self.greeting, name))
+            }),
+        )
+    }
+}
+#[doc(hidden)]
+#[allow(non_snake_case)]
+mod __Greeter_tools {
+    #[allow(unused_imports)]
+    use super::*;
+    use ollama_rs::re_exports::schemars;
+    use ollama_rs::re_exports::serde;
+    #[doc(hidden)]
+    #[allow(non_camel_case_types, missing_docs)]
+    #[serde(crate = "ollama_rs::re_exports::serde")]
+    pub struct __hello_world__Params {
+        ///Whom to say hello to
+        pub name: String,
+    }
+    #[allow(non_camel_case_types)]
+    pub struct hello_world(pub(super) ::std::sync::Arc<Greeter>);
+}
+impl ::ollama_rs::generation::tools::Tool for __Greeter_tools::hello_world {
+    type Params = __Greeter_tools::__hello_world__Params;
+    #[inline]
+    fn name() -> &'static str {
+        "hello_world"
+    }
+    #[inline]
+    fn description() -> &'static str {
+        "Say hello"
+    }
+    async fn call(
+        &mut self,
+        Self::Params { name }: Self::Params,
+    ) -> ::std::result::Result<
+        ::std::string::String,
+        ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>,
+    > {
+        #[allow(unused_imports)]
+        use ::ollama_rs::generation::tools::macro_support::{
+            BoxedToolError as _, DisplayToolError as _, Output, SerializeOutput as _,
+            StringOutput as _, ToolError,
+        };
+        let output = self.0.hello_world(name).await;
+        match output {
+            ::std::result::Result::Ok(output) => Output(output).into_tool_output(),
+            ::std::result::Result::Err(error) => {
+                ::std::result::Result::Err(ToolError(error).into_tool_error())
+            }
+        }
+    }
+}
+impl ::ollama_rs::generation::tools::ToolGroup for Greeter {
+    fn add_tools<C: ::ollama_rs::history::AsyncChatHistory>(
+        self: ::std::sync::Arc<Self>,
+        coordinator: ::ollama_rs::coordinator::Coordinator<C>,
+    ) -> ::ollama_rs::coordinator::Coordinator<C> {
+        let coordinator = coordinator
+            .add_tool(__Greeter_tools::hello_world(::std::sync::Arc::clone(&self)));
+        coordinator
+    }
+}
@@ -0,0 +1,23 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+struct Greeter {
+    greeting: String,
+}
+
+#[tools]
+impl Greeter {
+    fn new(greeting: String) -> Self {
+        Self { greeting }
+    }
+
+    /// Say hello
+    ///
+    /// * name - Whom to say hello to
+    async fn hello_world(
+        &self,
+        name: String,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(format!("{} {}", self.greeting, name))
+    }
+}
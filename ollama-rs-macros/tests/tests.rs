@@ -2,3 +2,8 @@
 pub fn pass() {
     macrotest::expand("tests/expand/*.rs");
 }
+
+#[test]
+pub fn ui() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,13 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+/// Get the weather for a given city
+#[derive(Tool)]
+#[tool(handler = get_weather)]
+struct GetWeather {
+    /// The city to get the weather for
+    city: String,
+    days: u8,
+}
+
+fn main() {}
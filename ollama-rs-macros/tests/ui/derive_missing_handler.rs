@@ -0,0 +1,11 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+/// Get the weather for a given city
+#[derive(Tool)]
+struct GetWeather {
+    /// The city to get the weather for
+    city: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+/// Say hello
+#[function(name = "hello", name = "hi")]
+async fn hello_world() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    Ok("Hello".to_string())
+}
+
+fn main() {}
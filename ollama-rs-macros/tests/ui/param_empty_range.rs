@@ -0,0 +1,14 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+/// Say hello
+///
+/// * times - How many times to say hello
+#[function]
+async fn hello_world(
+    #[param(range())] times: u32,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    Ok("Hello".repeat(times as usize))
+}
+
+fn main() {}
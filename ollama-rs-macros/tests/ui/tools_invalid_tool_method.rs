@@ -0,0 +1,20 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+struct Counter;
+
+#[tools]
+impl Counter {
+    /// Get the count
+    async fn count(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok("1".to_string())
+    }
+
+    /// Set the count
+    #[tool(name = "set_count")]
+    async fn set(&mut self, value: u32) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(value.to_string())
+    }
+}
+
+fn main() {}
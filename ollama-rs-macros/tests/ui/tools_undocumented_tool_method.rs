@@ -0,0 +1,19 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+struct Counter;
+
+#[tools]
+impl Counter {
+    /// Get the count
+    async fn count(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok("1".to_string())
+    }
+
+    #[tool(name = "reset")]
+    async fn reset(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok("0".to_string())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+struct Greeter;
+
+#[tools]
+impl Greeter {
+    /// Say hello
+    #[tool(rename = "hello")]
+    async fn hello_world(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok("Hello".to_string())
+    }
+}
+
+fn main() {}
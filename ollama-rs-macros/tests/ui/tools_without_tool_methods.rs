@@ -0,0 +1,14 @@
+#[macro_use]
+extern crate ollama_rs_macros;
+
+struct Greeter;
+
+#[tools]
+impl Greeter {
+    /// Say hello, but not as a tool since it is not async
+    fn hello_world(&self) -> String {
+        "Hello".to_string()
+    }
+}
+
+fn main() {}
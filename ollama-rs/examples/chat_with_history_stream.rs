@@ -3,8 +3,9 @@ use ollama_rs::{
     generation::chat::{request::ChatMessageRequest, ChatMessage, ChatMessageResponseStream},
     Ollama,
 };
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use tokio::io::{stdout, AsyncBufReadExt, AsyncWriteExt};
+use tokio::sync::Mutex;
 use tokio_stream::StreamExt;
 
 #[tokio::main]
@@ -46,7 +47,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    dbg!(&history.lock().unwrap());
+    dbg!(&history.lock().await);
 
     Ok(())
 }
@@ -1,8 +1,5 @@
 use ollama_rs::{
-    generation::{
-        completion::request::GenerationRequest,
-        parameters::{FormatType, JsonSchema, JsonStructure},
-    },
+    generation::{completion::request::GenerationRequest, parameters::JsonSchema},
     models::ModelOptions,
     Ollama,
 };
@@ -29,18 +26,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let model = "llama3.2:latest".to_string();
     let prompt = "Tell me about the country north of the USA".to_string();
 
-    let format = FormatType::StructuredJson(Box::new(JsonStructure::new::<Output>()));
-    dbg!(&format);
     let res = ollama
-        .generate(
-            GenerationRequest::new(model, prompt)
-                .format(format)
-                .options(ModelOptions::default().temperature(0.0)),
+        .generate_structured::<Output>(
+            GenerationRequest::new(model, prompt).options(ModelOptions::default().temperature(0.0)),
         )
         .await?;
 
-    dbg!(&res.response);
-    let resp: Output = serde_json::from_str(&res.response)?;
+    dbg!(&res.response.response);
+    let resp = res.value;
 
     // Output {
     //     country: "Canada",
@@ -0,0 +1,88 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ollama_rs::{coordinator::Coordinator, generation::chat::ChatMessage, Ollama};
+use tokio::sync::RwLock;
+
+/// A tiny key-value store whose methods are exposed as tools.
+///
+/// Tools generated by `#[ollama_rs::tools]` share this instance, so they can hold state such as
+/// a DB pool or an HTTP client.
+#[derive(Default)]
+struct Notes {
+    client: reqwest::Client,
+    notes: RwLock<HashMap<String, String>>,
+}
+
+#[ollama_rs::tools]
+impl Notes {
+    /// Save a note under a title, replacing any previous note with that title.
+    ///
+    /// * title - Title of the note.
+    /// * content - Content of the note.
+    async fn save_note(
+        &self,
+        title: String,
+        content: String,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self.notes.write().await.insert(title.clone(), content);
+        Ok(format!("Saved note '{title}'"))
+    }
+
+    /// Read the note saved under a title.
+    ///
+    /// * title - Title of the note.
+    async fn read_note(
+        &self,
+        title: String,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self
+            .notes
+            .read()
+            .await
+            .get(&title)
+            .cloned()
+            .unwrap_or_else(|| format!("No note named '{title}'")))
+    }
+
+    /// Get the weather for a given city.
+    ///
+    /// * city - City to get the weather for.
+    async fn get_weather(
+        &self,
+        city: String,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self
+            .client
+            .get(format!("https://wttr.in/{city}?format=%C+%t"))
+            .send()
+            .await?
+            .text()
+            .await?)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+    let ollama = Ollama::default();
+    let notes = Arc::new(Notes::default());
+
+    let mut coordinator =
+        Coordinator::new(ollama, "llama3.2".to_string(), vec![]).add_tool_group(notes.clone());
+
+    let user_messages = vec![
+        "What's the weather in Berlin? Save it in a note titled 'berlin'.",
+        "What does my note titled 'berlin' say?",
+    ];
+
+    for user_message in user_messages {
+        println!("User: {user_message}");
+
+        let user_message = ChatMessage::user(user_message.to_owned());
+        let resp = coordinator.chat(vec![user_message]).await?;
+        println!("Assistant: {}", resp.message.content);
+    }
+
+    println!("Notes: {:?}", notes.notes.read().await);
+
+    Ok(())
+}
@@ -1,35 +1,75 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use crate::{
     generation::{
         chat::{request::ChatMessageRequest, ChatMessage, ChatMessageResponse, MessageRole},
         parameters::{FormatType, KeepAlive, ThinkType},
-        tools::{Tool, ToolHolder, ToolInfo},
+        tools::{Tool, ToolGroup, ToolHolder, ToolInfo},
     },
-    history::ChatHistory,
+    history::AsyncChatHistory,
     models::ModelOptions,
     Ollama,
 };
 
+/// Controls whether the model may or must call tools on the next turn.
+///
+/// Ollama has no native `tool_choice`, so this is emulated by the [`Coordinator`]: forbidding
+/// tools withholds them from the request, while requiring a tool restricts the tools sent and adds
+/// a transient instruction to the request (it is not stored in the history).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ToolChoice {
+    /// The model decides whether to call any of the enabled tools.
+    #[default]
+    Auto,
+    /// No tools are sent to the model.
+    None,
+    /// The model is instructed to call one of the enabled tools.
+    Required,
+    /// The model is instructed to call the tool with this name, and only that tool is sent.
+    Tool(String),
+}
+
+/// Controls when the [`Coordinator`] format is set on requests that also carry tools.
+///
+/// Requests that carry no tools always get the format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FormatSequencing {
+    /// Set the format only once the last message in the history is a tool result. Ollama
+    /// otherwise won't call the tools if the format is set on the first request.
+    #[default]
+    AfterToolResults,
+    /// Set the format on every request, even when tools are available.
+    Always,
+    /// Never set the format on requests that carry tools.
+    WithoutTools,
+}
+
 /// A coordinator for managing chat interactions and tool usage.
 ///
 /// This struct is responsible for coordinating chat messages and tool
 /// interactions within the Ollama service. It maintains the state of the
 /// chat history, tools, and generation options.
-pub struct Coordinator<C: ChatHistory> {
+pub struct Coordinator<C: AsyncChatHistory> {
     model: String,
     ollama: Ollama,
     options: ModelOptions,
     history: C,
     tool_infos: Vec<ToolInfo>,
     tools: HashMap<String, Box<dyn ToolHolder>>,
+    toolsets: HashMap<String, String>,
+    disabled_toolsets: HashSet<String>,
+    tool_choice: ToolChoice,
     debug: bool,
     format: Option<FormatType>,
+    format_sequencing: FormatSequencing,
     keep_alive: Option<KeepAlive>,
     think: Option<ThinkType>,
 }
 
-impl<C: ChatHistory> Coordinator<C> {
+impl<C: AsyncChatHistory> Coordinator<C> {
     /// Creates a new `Coordinator` instance without tools.
     ///
     /// # Arguments
@@ -41,32 +81,103 @@ impl<C: ChatHistory> Coordinator<C> {
     /// # Returns
     ///
     /// A new `Coordinator` instance.
-    pub fn new(ollama: Ollama, model: String, history: C) -> Self {
+    pub fn new(ollama: Ollama, model: impl Into<String>, history: C) -> Self {
         Self {
-            model,
+            model: model.into(),
             ollama,
             options: ModelOptions::default(),
             history,
             tool_infos: Vec::default(),
             tools: HashMap::default(),
+            toolsets: HashMap::default(),
+            disabled_toolsets: HashSet::default(),
+            tool_choice: ToolChoice::default(),
             debug: false,
             format: None,
+            format_sequencing: FormatSequencing::default(),
             keep_alive: None,
             think: None,
         }
     }
 
+    /// Registers a tool under its instance name ([`Tool::tool_name`]).
+    ///
+    /// The same tool type can be registered several times as long as each instance has a
+    /// distinct name, see [`Tool::with_name`]. Registering a tool under a name that is already
+    /// taken replaces the previous tool.
     pub fn add_tool<T: Tool + 'static>(mut self, tool: T) -> Self {
-        self.tool_infos.push(ToolInfo::new::<_, T>());
-        self.tools.insert(T::name().to_string(), Box::new(tool));
+        let info = ToolInfo::from_tool(&tool);
+        let name = info.function.name.clone();
+
+        self.tool_infos.retain(|info| info.function.name != name);
+        self.tool_infos.push(info);
+        self.toolsets.remove(&name);
+        self.tools.insert(name, Box::new(tool));
         self
     }
 
+    /// Registers every tool of a [`ToolGroup`], such as an `impl` block annotated with
+    /// `#[tools]`, bound to the shared `group` instance.
+    pub fn add_tool_group<G: ToolGroup>(self, group: Arc<G>) -> Self {
+        group.add_tools(self)
+    }
+
+    /// The information of the registered tools, including the tools of disabled toolsets.
+    pub fn tool_infos(&self) -> &[ToolInfo] {
+        &self.tool_infos
+    }
+
+    /// Registers a tool as part of the named `toolset`.
+    ///
+    /// Toolsets are enabled by default and can be switched on and off between turns with
+    /// [`Coordinator::enable_toolset`] and [`Coordinator::disable_toolset`]. Tools registered with
+    /// [`Coordinator::add_tool`] don't belong to any toolset and are always enabled.
+    pub fn add_tool_to_toolset<T: Tool + 'static>(
+        self,
+        toolset: impl Into<String>,
+        tool: T,
+    ) -> Self {
+        let name = tool.tool_name().into_owned();
+        let mut coordinator = self.add_tool(tool);
+        coordinator.toolsets.insert(name, toolset.into());
+        coordinator
+    }
+
+    /// Enables the tools of `toolset` for the following turns.
+    pub fn enable_toolset(&mut self, toolset: &str) {
+        self.disabled_toolsets.remove(toolset);
+    }
+
+    /// Disables the tools of `toolset` for the following turns. Disabled tools are neither sent
+    /// to the model nor called.
+    pub fn disable_toolset(&mut self, toolset: impl Into<String>) {
+        self.disabled_toolsets.insert(toolset.into());
+    }
+
+    /// Returns whether the tools of `toolset` are currently enabled.
+    pub fn is_toolset_enabled(&self, toolset: &str) -> bool {
+        !self.disabled_toolsets.contains(toolset)
+    }
+
+    /// Sets the [`ToolChoice`] for the next call to [`Coordinator::chat`].
+    ///
+    /// The choice only applies to the first request of that turn; the requests made after tool
+    /// results, and the following turns, use [`ToolChoice::Auto`] again.
+    pub fn set_tool_choice(&mut self, tool_choice: ToolChoice) {
+        self.tool_choice = tool_choice;
+    }
+
     pub fn format(mut self, format: FormatType) -> Self {
         self.format = Some(format);
         self
     }
 
+    /// Sets when the format is set on requests that also carry tools.
+    pub fn format_sequencing(mut self, format_sequencing: FormatSequencing) -> Self {
+        self.format_sequencing = format_sequencing;
+        self
+    }
+
     pub fn options(mut self, options: ModelOptions) -> Self {
         self.options = options;
         self
@@ -98,36 +209,17 @@ impl<C: ChatHistory> Coordinator<C> {
             }
         }
 
-        let mut request = ChatMessageRequest::new(self.model.clone(), messages)
-            .options(self.options.clone())
-            .tools(self.tool_infos.clone());
-
-        if let Some(keep_alive) = &self.keep_alive {
-            request = request.keep_alive(keep_alive.clone());
-        }
-
-        if let Some(think) = &self.think {
-            request = request.think(think.clone());
-        }
-
-        if let Some(format) = &self.format {
-            // If no tools are specified, set the format on the request. Otherwise wait for the
-            // recursive call by checking that the last message in the history has a Tool role,
-            // before setting the format. Ollama otherwise won't call the tool if the format
-            // is set on the first request.
-            if self.tool_infos.is_empty() {
-                request = request.format(format.clone());
-            } else if let Some(last_message) = self.history.messages().last() {
-                if last_message.role == MessageRole::Tool {
-                    request = request.format(format.clone());
-                }
-            }
-        }
-
+        let (request, instruction) = self.build_request(messages).await?;
         let resp = self
             .ollama
-            .send_chat_messages_with_history(&mut self.history, request)
+            .send_chat_messages_with_history_and_instruction(
+                &mut self.history,
+                request,
+                instruction,
+            )
             .await?;
+        // The choice is only consumed once the request succeeds, so that the turn can be retried
+        self.tool_choice = ToolChoice::Auto;
 
         if !resp.message.tool_calls.is_empty() {
             for call in resp.message.tool_calls {
@@ -135,6 +227,10 @@ impl<C: ChatHistory> Coordinator<C> {
                     eprintln!("Tool call: {:?}", call.function); // TODO: Use log crate?
                 }
 
+                if !self.is_tool_enabled(&call.function.name) {
+                    return Err(crate::error::ToolCallError::UnknownToolName.into());
+                }
+
                 let Some(tool) = self.tools.get_mut(call.function.name.as_str()) else {
                     return Err(crate::error::ToolCallError::UnknownToolName.into());
                 };
@@ -148,7 +244,7 @@ impl<C: ChatHistory> Coordinator<C> {
                     eprintln!("Tool response: {}", &resp);
                 }
 
-                self.history.push(ChatMessage::tool(resp))
+                self.history.push_message(ChatMessage::tool(resp)).await?;
             }
 
             // recurse
@@ -164,4 +260,271 @@ impl<C: ChatHistory> Coordinator<C> {
             Ok(resp)
         }
     }
+
+    fn is_tool_enabled(&self, name: &str) -> bool {
+        self.toolsets
+            .get(name)
+            .is_none_or(|toolset| self.is_toolset_enabled(toolset))
+    }
+
+    /// Builds the request for the next turn, along with the transient instruction emulating
+    /// the [`ToolChoice`], if any.
+    async fn build_request(
+        &self,
+        messages: Vec<ChatMessage>,
+    ) -> crate::error::Result<(ChatMessageRequest, Option<String>)> {
+        let enabled_tools = self
+            .tool_infos
+            .iter()
+            .filter(|info| self.is_tool_enabled(&info.function.name))
+            .cloned();
+
+        let (tool_infos, instruction) = match &self.tool_choice {
+            ToolChoice::Auto => (enabled_tools.collect(), None),
+            ToolChoice::None => (Vec::new(), None),
+            ToolChoice::Required => (
+                enabled_tools.collect(),
+                Some("You must call one of the available tools to answer.".to_string()),
+            ),
+            ToolChoice::Tool(name) => {
+                let tool_infos: Vec<ToolInfo> = enabled_tools
+                    .filter(|info| info.function.name == *name)
+                    .collect();
+                if tool_infos.is_empty() {
+                    return Err(crate::error::ToolCallError::UnknownToolName.into());
+                }
+
+                (
+                    tool_infos,
+                    Some(format!("You must call the `{name}` tool to answer.")),
+                )
+            }
+        };
+
+        let mut request =
+            ChatMessageRequest::new(self.model.clone(), messages).options(self.options.clone());
+
+        if let Some(keep_alive) = &self.keep_alive {
+            request = request.keep_alive(keep_alive.clone());
+        }
+
+        if let Some(think) = &self.think {
+            request = request.think(think.clone());
+        }
+
+        if let Some(format) = &self.format {
+            let set_format = tool_infos.is_empty()
+                || match self.format_sequencing {
+                    FormatSequencing::Always => true,
+                    FormatSequencing::WithoutTools => false,
+                    FormatSequencing::AfterToolResults => self
+                        .history
+                        .fetch_messages()
+                        .await?
+                        .last()
+                        .is_some_and(|last_message| last_message.role == MessageRole::Tool),
+                };
+
+            if set_format {
+                request = request.format(format.clone());
+            }
+        }
+
+        Ok((request.tools(tool_infos), instruction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{generation::parameters::JsonStructure, test_util::Response};
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, JsonSchema)]
+    struct EchoParams {
+        text: String,
+    }
+
+    struct Echo;
+
+    impl Tool for Echo {
+        type Params = EchoParams;
+
+        fn name() -> &'static str {
+            "echo"
+        }
+
+        fn description() -> &'static str {
+            "Echoes the text back"
+        }
+
+        async fn call(
+            &mut self,
+            parameters: Self::Params,
+        ) -> crate::generation::tools::Result<String> {
+            Ok(parameters.text)
+        }
+    }
+
+    fn coordinator() -> Coordinator<Vec<ChatMessage>> {
+        Coordinator::new(Ollama::default(), "model".to_string(), Vec::new())
+            .add_tool(Echo)
+            .add_tool_to_toolset("search", Echo.with_name("search_web"))
+            .add_tool_to_toolset("search", Echo.with_name("search_news"))
+    }
+
+    fn tool_names(request: &ChatMessageRequest) -> Vec<&str> {
+        request
+            .tools
+            .iter()
+            .map(|info| info.function.name.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn disabled_toolsets_are_not_sent() {
+        let mut coordinator = coordinator();
+
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert_eq!(tool_names(&request), ["echo", "search_web", "search_news"]);
+
+        coordinator.disable_toolset("search");
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert_eq!(tool_names(&request), ["echo"]);
+        assert!(!coordinator.is_tool_enabled("search_web"));
+
+        coordinator.enable_toolset("search");
+        assert!(coordinator.is_tool_enabled("search_web"));
+    }
+
+    fn response() -> serde_json::Value {
+        serde_json::json!({
+            "model": "model",
+            "created_at": "2026-01-01T00:00:00Z",
+            "message": { "role": "assistant", "content": "done" },
+            "done": true,
+        })
+    }
+
+    #[tokio::test]
+    async fn tool_choice_applies_to_the_next_request_only() {
+        let mut coordinator = coordinator();
+
+        coordinator.set_tool_choice(ToolChoice::Tool("search_news".to_string()));
+        let (request, instruction) = coordinator.build_request(vec![]).await.unwrap();
+        assert_eq!(tool_names(&request), ["search_news"]);
+        assert!(instruction.unwrap().contains("search_news"));
+
+        coordinator.set_tool_choice(ToolChoice::None);
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert!(request.tools.is_empty());
+
+        coordinator.disable_toolset("search");
+        coordinator.set_tool_choice(ToolChoice::Tool("search_news".to_string()));
+        assert!(coordinator.build_request(vec![]).await.is_err());
+
+        // The invalid choice is kept, and applies once the tool is enabled
+        coordinator.enable_toolset("search");
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert_eq!(tool_names(&request), ["search_news"]);
+
+        // A successful turn consumes the choice
+        let (ollama, _) = crate::test_util::serve_in_order(vec![response()]).await;
+        coordinator.ollama = ollama;
+        coordinator
+            .chat(vec![ChatMessage::user("hi".to_string())])
+            .await
+            .unwrap();
+        let (request, instruction) = coordinator.build_request(vec![]).await.unwrap();
+        assert_eq!(tool_names(&request).len(), 3);
+        assert!(instruction.is_none());
+    }
+
+    #[tokio::test]
+    async fn tool_choice_is_kept_when_the_request_fails() {
+        let mut failed = false;
+        let (ollama, mut requests) = crate::test_util::serve(move |_| {
+            if std::mem::replace(&mut failed, true) {
+                Response::json(response())
+            } else {
+                Response::json(serde_json::json!({ "error": "overloaded" })).status(503)
+            }
+        })
+        .await;
+        let mut coordinator = Coordinator::new(ollama, "model", Vec::new())
+            .add_tool(Echo)
+            .add_tool(Echo.with_name("search"));
+
+        coordinator.set_tool_choice(ToolChoice::Tool("search".to_string()));
+        assert!(coordinator.chat(vec![]).await.is_err());
+        coordinator.chat(vec![]).await.unwrap();
+
+        for _ in 0..2 {
+            let request = requests.recv().await.unwrap().body;
+            assert_eq!(request["tools"].as_array().unwrap().len(), 1);
+            assert_eq!(request["tools"][0]["function"]["name"], "search");
+        }
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert_eq!(tool_names(&request), ["echo", "search"]);
+    }
+
+    #[tokio::test]
+    async fn tool_choice_instruction_is_not_stored() {
+        let (ollama, mut requests) = crate::test_util::serve_in_order(vec![response()]).await;
+        let mut coordinator = Coordinator::new(ollama, "model", Vec::new()).add_tool(Echo);
+
+        coordinator.set_tool_choice(ToolChoice::Required);
+        coordinator
+            .chat(vec![ChatMessage::user("hi".to_string())])
+            .await
+            .unwrap();
+
+        let request = requests.recv().await.unwrap().body;
+        let messages = request["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1]["role"], "system");
+        let history = coordinator
+            .history
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(history, ["hi", "done"]);
+    }
+
+    #[tokio::test]
+    async fn format_sequencing() {
+        #[allow(dead_code)]
+        #[derive(JsonSchema)]
+        struct Answer {
+            answer: String,
+        }
+
+        let format = FormatType::StructuredJson(Box::new(JsonStructure::new::<Answer>()));
+        let mut coordinator = coordinator().format(format);
+
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert!(request.format.is_none());
+
+        coordinator
+            .history
+            .push(ChatMessage::tool("result".to_string()));
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert!(request.format.is_some());
+
+        let mut coordinator = coordinator.format_sequencing(FormatSequencing::WithoutTools);
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert!(request.format.is_none());
+
+        coordinator.set_tool_choice(ToolChoice::None);
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert!(request.format.is_some());
+
+        let mut coordinator = coordinator.format_sequencing(FormatSequencing::Always);
+        coordinator.history.clear();
+        let (request, _) = coordinator.build_request(vec![]).await.unwrap();
+        assert!(request.format.is_some());
+    }
 }
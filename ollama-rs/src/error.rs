@@ -2,7 +2,11 @@ use serde::Deserialize;
 use static_assertions::assert_impl_all;
 use thiserror::Error;
 
+use crate::generation::structured::SchemaViolation;
+
 assert_impl_all!(OllamaError: Send, Sync);
+assert_impl_all!(HistoryError: Send, Sync);
+assert_impl_all!(ModelNameError: Send, Sync);
 /// A result type for operations in the ollama-rs crate.
 ///
 /// This type is used throughout the crate to represent the result of an operation,
@@ -23,6 +27,23 @@ pub enum OllamaError {
     ReqwestError(#[from] reqwest::Error),
     #[error("Internal Ollama error: {}", .0.message)]
     InternalError(InternalOllamaError),
+    #[error("Chat history error")]
+    HistoryError(#[from] HistoryError),
+    #[error("Invalid model name")]
+    ModelNameError(#[from] ModelNameError),
+    #[error("The model response doesn't match the requested structure")]
+    StructuredOutputError {
+        /// The content of the last response.
+        content: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("The model response doesn't satisfy the requested schema")]
+    SchemaViolationError {
+        /// The content of the last response.
+        content: String,
+        violations: Vec<SchemaViolation>,
+    },
     #[error("{0}")]
     Other(String),
 }
@@ -51,3 +72,39 @@ pub enum ToolCallError {
     #[error("Tool errored internally when it was called")]
     InternalToolError(#[from] Box<dyn std::error::Error + Send + Sync>),
 }
+
+/// An error type for persistent chat histories.
+///
+/// This enum represents errors that can occur when storing, loading, listing, deleting or
+/// converting conversations.
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("History IO error")]
+    IoError(#[from] std::io::Error),
+    #[error("History JSON error")]
+    JsonError(#[from] serde_json::Error),
+    #[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error")]
+    SqliteError(#[from] rusqlite::Error),
+    #[error("Invalid conversation ID `{0}`, expected ASCII letters, digits, `_` and `-`")]
+    InvalidConversationId(String),
+    #[error("Conversation `{0}` not found")]
+    ConversationNotFound(String),
+    #[error("Conversation `{0}` already exists")]
+    ConversationExists(String),
+    #[error("Invalid transcript: {0}")]
+    InvalidTranscript(String),
+    #[error("Invalid conversation tree: {0}")]
+    InvalidConversationTree(String),
+    #[error("History backend error")]
+    BackendError(#[from] Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// An error returned when parsing an invalid model name.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid model name `{name}`: {reason}")]
+pub struct ModelNameError {
+    pub name: String,
+    pub reason: String,
+}
@@ -4,8 +4,12 @@
 /// of generation tasks, including chat, completion, embeddings, images,
 /// options, parameters, and tools.
 pub mod chat;
+pub mod classify;
 pub mod completion;
+pub mod defaults;
 pub mod embeddings;
 pub mod images;
+pub mod logprobs;
 pub mod parameters;
+pub mod structured;
 pub mod tools;
@@ -2,7 +2,13 @@ use serde::{Deserialize, Serialize};
 
 use super::{images::Image, tools::ToolCall};
 use crate::{
-    error::OllamaError, generation::parameters::LogprobsData, history::ChatHistory, Ollama,
+    error::OllamaError,
+    generation::{
+        defaults::RequestDefaults,
+        parameters::{DoneReason, LogprobsData},
+    },
+    history::AsyncChatHistory,
+    Ollama,
 };
 use request::ChatMessageRequest;
 
@@ -11,7 +17,10 @@ use request::ChatMessageRequest;
 use async_stream::stream;
 #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
 #[cfg(feature = "stream")]
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+#[cfg(feature = "stream")]
+use tokio::sync::Mutex;
 #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
 #[cfg(feature = "stream")]
 use tokio_stream::StreamExt;
@@ -33,7 +42,7 @@ impl Ollama {
         &self,
         request: ChatMessageRequest,
     ) -> crate::error::Result<ChatMessageResponseStream> {
-        let mut request = request;
+        let mut request = self.apply_defaults(request);
         request.stream = true;
 
         let url = format!("{}api/chat", self.url_str());
@@ -114,11 +123,36 @@ impl Ollama {
 
     /// Chat message generation.
     /// Returns a `ChatMessageResponse` object
+    ///
+    /// With [`ChatMessageRequest::auto_continue`], a response that is cut off is continued by
+    /// follow-up requests, and the returned response holds the whole output.
     pub async fn send_chat_messages(
         &self,
         request: ChatMessageRequest,
     ) -> crate::error::Result<ChatMessageResponse> {
-        let mut request = request;
+        let mut response = self.send_chat_messages_once(request.clone()).await?;
+
+        let max_continuations = request.auto_continue.unwrap_or(0);
+        let mut continuations = 0;
+        while response.done_reason == Some(DoneReason::Length) && continuations < max_continuations
+        {
+            // The model continues the last assistant message
+            let mut follow_up = request.clone();
+            follow_up.messages.push(response.message.clone());
+            let continuation = self.send_chat_messages_once(follow_up).await?;
+
+            response.append(continuation);
+            continuations += 1;
+        }
+
+        Ok(response)
+    }
+
+    async fn send_chat_messages_once(
+        &self,
+        request: ChatMessageRequest,
+    ) -> crate::error::Result<ChatMessageResponse> {
+        let mut request = self.apply_defaults(request);
         request.stream = false;
 
         let url = format!("{}api/chat", self.url_str());
@@ -145,7 +179,12 @@ impl Ollama {
 impl Ollama {
     #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
     #[cfg(feature = "stream")]
-    pub async fn send_chat_messages_with_history_stream<C: ChatHistory + Send + 'static>(
+    /// Chat message generation with streaming, storing the messages in `history`.
+    ///
+    /// The history is locked while the request is prepared, and again when the response is
+    /// complete. If the response can't be stored, an error is yielded right before the final
+    /// response.
+    pub async fn send_chat_messages_with_history_stream<C: AsyncChatHistory + Send + 'static>(
         &self,
         history: Arc<Mutex<C>>,
         mut request: ChatMessageRequest,
@@ -155,13 +194,13 @@ impl Ollama {
 
         // The request is modified to include the current chat messages
         {
-            let mut hist = history.lock().unwrap();
-            for m in request.messages {
-                hist.push(m);
+            let mut hist = history.lock().await;
+            for m in std::mem::take(&mut request.messages) {
+                hist.push_message(m).await?;
             }
-        }
 
-        request.messages = history.lock().unwrap().messages().to_vec();
+            request.messages = self.history_messages(&mut *hist, &request).await?;
+        }
         request.stream = true;
 
         let mut resp_stream: ChatMessageResponseStream =
@@ -170,15 +209,23 @@ impl Ollama {
         let s = stream! {
             let mut result = String::new();
 
-            while let Some(item) = resp_stream.try_next().await.unwrap() {
-                let mut item = item;
+            while let Some(item) = resp_stream.next().await {
+                let Ok(mut item) = item else {
+                    yield Err(());
+                    break;
+                };
                 let msg_part = item.message.content.clone();
 
                 result.push_str(&msg_part);
 
                 if item.done {
                     item.message.content = result.clone();
-                    history.lock().unwrap().push(item.message.clone());
+                    if let Err(e) = history.lock().await.push_message(item.message.clone()).await {
+                        // Yielded before the final response, so that callers stopping at it
+                        // still see the error
+                        log::error!("Failed to store the response in the history: {e}");
+                        yield Err(());
+                    }
                     result.clear();
                 }
 
@@ -191,22 +238,37 @@ impl Ollama {
 
     /// Chat message generation
     /// Returns a `ChatMessageResponse` object
-    pub async fn send_chat_messages_with_history<C: ChatHistory>(
+    pub async fn send_chat_messages_with_history<C: AsyncChatHistory>(
+        &self,
+        history: &mut C,
+        request: ChatMessageRequest,
+    ) -> crate::error::Result<ChatMessageResponse> {
+        self.send_chat_messages_with_history_and_instruction(history, request, None)
+            .await
+    }
+
+    /// Like [`Ollama::send_chat_messages_with_history`], with a system `instruction` sent after
+    /// the history, which only applies to this request and is not stored.
+    pub(crate) async fn send_chat_messages_with_history_and_instruction<C: AsyncChatHistory>(
         &self,
         history: &mut C,
         mut request: ChatMessageRequest,
+        instruction: Option<String>,
     ) -> crate::error::Result<ChatMessageResponse> {
         // The request is modified to include the current chat messages
-        for m in request.messages {
-            history.push(m);
+        for m in std::mem::take(&mut request.messages) {
+            history.push_message(m).await?;
         }
 
-        request.messages = history.messages().to_vec();
+        request.messages = self.history_messages(history, &request).await?;
+        if let Some(instruction) = instruction {
+            request.messages.push(ChatMessage::system(instruction));
+        }
 
         let result = self.send_chat_messages(request.clone()).await;
 
         if let Ok(result) = result {
-            history.push(result.message.clone());
+            history.push_message(result.message.clone()).await?;
 
             return Ok(result);
         }
@@ -215,6 +277,52 @@ impl Ollama {
     }
 }
 
+impl Ollama {
+    /// The messages of `history` to send with `request`, trimmed if the history has a
+    /// [`Trimming`](crate::history::Trimming).
+    pub(crate) async fn history_messages<C: AsyncChatHistory>(
+        &self,
+        history: &mut C,
+        request: &ChatMessageRequest,
+    ) -> crate::error::Result<Vec<ChatMessage>> {
+        // The `num_ctx` of the defaults also limits the history
+        let options = self
+            .defaults_for(&request.model_name)
+            .merge(&RequestDefaults {
+                options: request.options.clone(),
+                ..Default::default()
+            })
+            .options;
+
+        let needs_context_length = history.trimming().is_some_and(|trimming| {
+            trimming.needs_context_length(&request.model_name, options.as_ref())
+        });
+        if needs_context_length {
+            let context_length = self.model_context_length(&request.model_name).await?;
+            if let Some(trimming) = history.trimming() {
+                trimming.set_context_length(&request.model_name, context_length);
+            }
+        }
+
+        let messages = history.fetch_messages().await?;
+        Ok(match history.trimming() {
+            Some(trimming) => trimming.trim(messages, &request.model_name, options.as_ref()),
+            None => messages,
+        })
+    }
+
+    async fn model_context_length(&self, model: &str) -> crate::error::Result<usize> {
+        let info = self.show_model_info(model.to_string()).await?;
+        let context_length = info.context_length().ok_or_else(|| {
+            OllamaError::Other(format!(
+                "The context length of {model} is unknown, set `num_ctx` to trim the history"
+            ))
+        })?;
+
+        Ok(context_length as usize)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessageResponse {
     /// The name of the model used for the completion.
@@ -226,11 +334,48 @@ pub struct ChatMessageResponse {
     /// The log probabilities (only if `logprobs` is set to `true`)
     pub logprobs: Option<Vec<LogprobsData>>,
     pub done: bool,
+    /// Why the model stopped generating. This is only present if the completion is done.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done_reason: Option<DoneReason>,
     #[serde(flatten)]
     /// The final data of the completion. This is only present if the completion is done.
     pub final_data: Option<ChatMessageFinalResponseData>,
 }
 
+impl ChatMessageResponse {
+    /// Appends the output of a continuation of this response.
+    fn append(&mut self, continuation: ChatMessageResponse) {
+        self.message.content.push_str(&continuation.message.content);
+        if let Some(thinking) = continuation.message.thinking {
+            self.message
+                .thinking
+                .get_or_insert_with(String::new)
+                .push_str(&thinking);
+        }
+        self.message
+            .tool_calls
+            .extend(continuation.message.tool_calls);
+        if let Some(logprobs) = continuation.logprobs {
+            self.logprobs.get_or_insert_with(Vec::new).extend(logprobs);
+        }
+
+        self.final_data = match (self.final_data.take(), continuation.final_data) {
+            (Some(data), Some(continuation)) => Some(ChatMessageFinalResponseData {
+                total_duration: data.total_duration + continuation.total_duration,
+                load_duration: data.load_duration + continuation.load_duration,
+                prompt_eval_count: data.prompt_eval_count + continuation.prompt_eval_count,
+                prompt_eval_duration: data.prompt_eval_duration + continuation.prompt_eval_duration,
+                eval_count: data.eval_count + continuation.eval_count,
+                eval_duration: data.eval_duration + continuation.eval_duration,
+            }),
+            (data, continuation) => continuation.or(data),
+        };
+        self.created_at = continuation.created_at;
+        self.done = continuation.done;
+        self.done_reason = continuation.done_reason;
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessageFinalResponseData {
     /// Time spent generating the response
@@ -314,12 +459,16 @@ pub enum MessageRole {
 
 #[cfg(all(test, feature = "stream"))]
 mod tests {
-    use std::sync::{Arc, Mutex};
+    use std::sync::Arc;
 
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        sync::Mutex,
+    };
     use tokio_stream::StreamExt;
 
     use super::*;
+    use crate::test_util::serve_in_order;
 
     #[tokio::test]
     async fn streamed_history_preserves_final_message_tool_calls() {
@@ -390,10 +539,184 @@ mod tests {
         assert_eq!(final_chunk.message.content, "hello world");
         assert_eq!(final_chunk.message.tool_calls.len(), 1);
 
-        let history = history.lock().expect("history lock");
+        let history = history.lock().await;
         assert_eq!(history.len(), 2);
         assert_eq!(history[1].content, "hello world");
         assert_eq!(history[1].tool_calls.len(), 1);
         assert_eq!(history[1].tool_calls[0].function.name, "test_tool");
     }
+
+    /// A history behind an async API, such as a database or a remote service.
+    struct RemoteHistory {
+        messages: Arc<Mutex<Vec<ChatMessage>>>,
+    }
+
+    impl AsyncChatHistory for RemoteHistory {
+        async fn push_message(
+            &mut self,
+            message: ChatMessage,
+        ) -> Result<(), crate::error::HistoryError> {
+            tokio::task::yield_now().await;
+            self.messages.lock().await.push(message);
+            Ok(())
+        }
+
+        async fn fetch_messages(&self) -> Result<Vec<ChatMessage>, crate::error::HistoryError> {
+            tokio::task::yield_now().await;
+            Ok(self.messages.lock().await.clone())
+        }
+    }
+
+    /// A history that can't store the responses of the model.
+    struct ReadOnlyHistory(Vec<ChatMessage>);
+
+    impl AsyncChatHistory for ReadOnlyHistory {
+        async fn push_message(
+            &mut self,
+            message: ChatMessage,
+        ) -> Result<(), crate::error::HistoryError> {
+            if message.role == MessageRole::Assistant {
+                return Err(std::io::Error::other("read-only").into());
+            }
+            self.0.push(message);
+            Ok(())
+        }
+
+        async fn fetch_messages(&self) -> Result<Vec<ChatMessage>, crate::error::HistoryError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn streamed_history_errors_are_yielded() {
+        let (ollama, _) = serve_in_order(vec![serde_json::json!({
+            "model": "test",
+            "created_at": "2026-01-01T00:00:00Z",
+            "message": { "role": "assistant", "content": "hello" },
+            "done": true,
+        })])
+        .await;
+        let history = Arc::new(Mutex::new(ReadOnlyHistory(Vec::new())));
+        let request = ChatMessageRequest::new(
+            "test".to_string(),
+            vec![ChatMessage::user("hi".to_string())],
+        );
+
+        let stream = ollama
+            .send_chat_messages_with_history_stream(history.clone(), request)
+            .await
+            .expect("stream starts");
+        let items: Vec<_> = stream.collect().await;
+
+        assert_eq!(items.len(), 2);
+        assert!(items[0].is_err());
+        assert_eq!(items[1].as_ref().unwrap().message.content, "hello");
+        assert_eq!(history.lock().await.0.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn async_history_stores_messages() {
+        let (ollama, _) = serve_in_order(vec![serde_json::json!({
+            "model": "test",
+            "created_at": "2026-01-01T00:00:00Z",
+            "message": { "role": "assistant", "content": "hello" },
+            "done": true,
+        })])
+        .await;
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let mut history = RemoteHistory {
+            messages: messages.clone(),
+        };
+        let request = ChatMessageRequest::new(
+            "test".to_string(),
+            vec![ChatMessage::user("hi".to_string())],
+        );
+
+        let response = ollama
+            .send_chat_messages_with_history(&mut history, request)
+            .await
+            .expect("chat succeeds");
+
+        assert_eq!(response.message.content, "hello");
+        let messages = messages.lock().await;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "hi");
+        assert_eq!(messages[1].content, "hello");
+    }
+
+    /// A history that can't be sent to another thread.
+    #[derive(Default)]
+    struct LocalHistory(std::rc::Rc<std::cell::RefCell<Vec<ChatMessage>>>);
+
+    impl crate::history::ChatHistory for LocalHistory {
+        fn push(&mut self, message: ChatMessage) {
+            self.0.borrow_mut().push(message);
+        }
+
+        fn messages(&self) -> std::borrow::Cow<'_, [ChatMessage]> {
+            std::borrow::Cow::Owned(self.0.borrow().clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn histories_do_not_have_to_be_send() {
+        let (ollama, _) = serve_in_order(vec![serde_json::json!({
+            "model": "test",
+            "created_at": "2026-01-01T00:00:00Z",
+            "message": { "role": "assistant", "content": "hello" },
+            "done": true,
+        })])
+        .await;
+        let mut history = LocalHistory::default();
+
+        ollama
+            .send_chat_messages_with_history(
+                &mut history,
+                ChatMessageRequest::new("test", vec![ChatMessage::user("hi".to_string())]),
+            )
+            .await
+            .expect("chat succeeds");
+        assert_eq!(history.0.borrow().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn cut_off_messages_are_continued() {
+        let (ollama, mut requests) = serve_in_order(vec![
+            serde_json::json!({
+                "model": "test",
+                "created_at": "2026-01-01T00:00:00Z",
+                "message": { "role": "assistant", "content": "Once upon" },
+                "done": true,
+                "done_reason": "length",
+            }),
+            serde_json::json!({
+                "model": "test",
+                "created_at": "2026-01-01T00:00:00Z",
+                "message": { "role": "assistant", "content": " a time." },
+                "done": true,
+                "done_reason": "stop",
+            }),
+        ])
+        .await;
+
+        let request = ChatMessageRequest::new(
+            "test".to_string(),
+            vec![ChatMessage::user("Tell me a story".to_string())],
+        )
+        .auto_continue(1);
+
+        let response = ollama
+            .send_chat_messages(request)
+            .await
+            .expect("chat succeeds");
+        assert_eq!(response.message.content, "Once upon a time.");
+        assert_eq!(response.done_reason, Some(DoneReason::Stop));
+
+        requests.recv().await.unwrap();
+        let follow_up = requests.recv().await.unwrap().body;
+        let messages = follow_up["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"], "Once upon");
+    }
 }
@@ -35,12 +35,16 @@ pub struct ChatMessageRequest {
     pub logprobs: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_logprobs: Option<u32>,
+    /// The maximum number of follow-up requests when the response is cut off, see
+    /// [`ChatMessageRequest::auto_continue`].
+    #[serde(skip)]
+    pub auto_continue: Option<u32>,
 }
 
 impl ChatMessageRequest {
-    pub fn new(model_name: String, messages: Vec<ChatMessage>) -> Self {
+    pub fn new(model_name: impl Into<String>, messages: Vec<ChatMessage>) -> Self {
         Self {
-            model_name,
+            model_name: model_name.into(),
             messages,
             options: None,
             template: None,
@@ -52,6 +56,7 @@ impl ChatMessageRequest {
             think: None,
             logprobs: None,
             top_logprobs: None,
+            auto_continue: None,
         }
     }
 
@@ -87,13 +92,14 @@ impl ChatMessageRequest {
 
     /// Add a tool definition that is available to the LLM.
     ///
-    /// This consumes the tool value only to identify its schema. The request does
-    /// not retain or execute the tool. When using
+    /// This consumes the tool value only to identify its name, description and schema,
+    /// including instance-level overrides. The request does not retain or execute the
+    /// tool. When using
     /// [`Ollama::send_chat_messages_stream`](crate::Ollama::send_chat_messages_stream),
     /// callers are responsible for consuming streamed tool calls and appending
     /// tool results to the next request.
-    pub fn add_tool<T: Tool>(mut self, _tool: T) -> Self {
-        self.tools.push(ToolInfo::new::<_, T>());
+    pub fn add_tool<T: Tool>(mut self, tool: T) -> Self {
+        self.tools.push(ToolInfo::from_tool(&tool));
         self
     }
 
@@ -114,6 +120,18 @@ impl ChatMessageRequest {
         self.top_logprobs = Some(top_logprobs);
         self
     }
+
+    /// Continues the response when it is cut off by the `num_predict` limit or the context
+    /// length, with up to `max_continuations` follow-up requests whose output is appended to the
+    /// response. This only applies to [`Ollama::send_chat_messages`](crate::Ollama::send_chat_messages)
+    /// and the methods built on it.
+    ///
+    /// The follow-up requests end with the response so far as an assistant message, which the
+    /// model continues.
+    pub fn auto_continue(mut self, max_continuations: u32) -> Self {
+        self.auto_continue = Some(max_continuations);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -238,4 +256,33 @@ mod tests {
         );
         assert!(function.contains_key("parameters"));
     }
+
+    #[test]
+    fn add_tool_uses_instance_level_overrides() {
+        let request = ChatMessageRequest::new(
+            "model".to_string(),
+            vec![ChatMessage::user("hello".to_string())],
+        )
+        .add_tool(TestTool)
+        .add_tool(
+            TestTool
+                .with_name("test_weather_eu")
+                .with_description("Gets test weather in Europe"),
+        );
+
+        let names = request
+            .tools
+            .iter()
+            .map(|info| info.function.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["test_weather", "test_weather_eu"]);
+        assert_eq!(
+            request.tools[1].function.description,
+            "Gets test weather in Europe"
+        );
+        assert_eq!(
+            request.tools[0].function.parameters,
+            request.tools[1].function.parameters
+        );
+    }
 }
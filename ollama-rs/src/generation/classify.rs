@@ -0,0 +1,301 @@
+//! Classification of a text into the variants of an enum, with the probability of each variant.
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    error::OllamaError,
+    generation::{
+        completion::{request::GenerationRequest, GenerationResponse},
+        logprobs::LogprobsAnalysis,
+        parameters::{JsonStructure, LogprobsData},
+        structured::StructuredOptions,
+    },
+    Ollama,
+};
+
+/// The number of alternatives requested for each token.
+const TOP_LOGPROBS: u32 = 20;
+
+/// The label chosen by the model, with the probability of every label.
+#[derive(Debug, Clone)]
+pub struct Classification<E> {
+    pub label: E,
+    /// Every label with its probability, most likely first. The probabilities sum to 1.
+    pub probabilities: Vec<(E, f64)>,
+    pub response: GenerationResponse,
+}
+
+impl Ollama {
+    /// Classifies `text` into one of the variants of the enum `E`, whose variants must not hold
+    /// data.
+    ///
+    /// The response is constrained to the labels of `E`. The probability of each label is taken
+    /// from the top alternatives of the first token where the labels differ, normalized over the
+    /// alternatives that start a label. Labels sharing that token are told apart by the
+    /// alternatives of the following tokens. A label that isn't among the alternatives gets a
+    /// probability of 0.
+    pub async fn classify<E: JsonSchema + DeserializeOwned>(
+        &self,
+        model: impl Into<String>,
+        text: impl Into<String>,
+    ) -> crate::error::Result<Classification<E>> {
+        let structure = JsonStructure::new::<E>();
+        let mut labels = Vec::new();
+        collect_labels(structure.schema().as_value(), &mut labels);
+        if labels.is_empty() {
+            return Err(OllamaError::Other(
+                "Classification requires an enum whose variants don't hold data".to_string(),
+            ));
+        }
+
+        let prompt = format!(
+            "Classify the following text as one of: {}.\n\nText: {}",
+            labels.join(", "),
+            text.into()
+        );
+        let request = GenerationRequest::new(model, prompt)
+            .logprobs(true)
+            .top_logprobs(TOP_LOGPROBS);
+        let structured = self
+            .generate_structured_with_options::<E>(request, StructuredOptions::new().max_retries(0))
+            .await?;
+
+        let chosen = serde_json::from_str::<String>(&structured.response.response)?;
+        let distribution = label_distribution(
+            &labels,
+            &chosen,
+            structured.response.logprobs.as_deref().unwrap_or_default(),
+        );
+
+        let mut probabilities = labels
+            .iter()
+            .zip(distribution)
+            .map(|(label, probability)| Ok((parse_label(label)?, probability)))
+            .collect::<crate::error::Result<Vec<_>>>()?;
+        probabilities.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        Ok(Classification {
+            label: structured.value,
+            probabilities,
+            response: structured.response,
+        })
+    }
+}
+
+fn parse_label<E: DeserializeOwned>(label: &str) -> crate::error::Result<E> {
+    Ok(serde_json::from_value(Value::String(label.to_string()))?)
+}
+
+/// Collects the string values allowed by a schema.
+fn collect_labels(schema: &Value, labels: &mut Vec<String>) {
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        labels.extend(values.iter().filter_map(Value::as_str).map(str::to_string));
+    }
+    if let Some(Value::String(value)) = schema.get("const") {
+        labels.push(value.clone());
+    }
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(schemas)) = schema.get(keyword) {
+            for schema in schemas {
+                collect_labels(schema, labels);
+            }
+        }
+    }
+}
+
+/// The probability of each label, from the alternatives of the tokens where the labels differ.
+///
+/// The alternatives of the first distinguishing token give the probability of each group of
+/// labels it starts. When several labels start with the chosen token, the following tokens split
+/// the probability of that group, until one label is left.
+fn label_distribution(labels: &[String], chosen: &str, logprobs: &[LogprobsData]) -> Vec<f64> {
+    // The labels as the model writes them, as JSON strings
+    let encoded = labels
+        .iter()
+        .map(|label| Value::String(label.clone()).to_string())
+        .collect::<Vec<_>>();
+    let consistent_with = |text: &str| {
+        let text = text.trim_start();
+        encoded
+            .iter()
+            .map(|label| label.starts_with(text) || text.starts_with(label.as_str()))
+            .collect::<Vec<_>>()
+    };
+    let common = common_prefix_len(&encoded);
+
+    let mut weights = vec![0.0; labels.len()];
+    // The labels consistent with the output so far, and their probability
+    let mut active = vec![true; labels.len()];
+    let mut mass = 1.0;
+    let mut analyzed = false;
+    let mut text_before = String::new();
+
+    for (index, data) in logprobs.iter().enumerate() {
+        let text = logprobs[..=index].text();
+        if text.trim_start().len() <= common {
+            text_before = text;
+            continue;
+        }
+        if active.iter().filter(|active| **active).count() <= 1 {
+            break;
+        }
+
+        let mut alternatives = data
+            .top_logprobs
+            .iter()
+            .map(|alternative| (alternative.token.as_str(), alternative.logprob))
+            .collect::<Vec<_>>();
+        if !alternatives.iter().any(|(token, _)| *token == data.token) {
+            alternatives.push((&data.token, data.logprob));
+        }
+
+        // Split the probability of each alternative among the labels it is consistent with
+        let mut step = vec![0.0; labels.len()];
+        for (token, logprob) in alternatives {
+            let consistent = consistent_with(&format!("{text_before}{token}"));
+            let consistent = consistent
+                .iter()
+                .zip(&active)
+                .map(|(consistent, active)| *consistent && *active)
+                .collect::<Vec<_>>();
+            let count = consistent.iter().filter(|consistent| **consistent).count();
+            for (step, consistent) in step.iter_mut().zip(consistent) {
+                if consistent {
+                    *step += logprob.exp() / count as f64;
+                }
+            }
+        }
+        let total = step.iter().sum::<f64>();
+        if total <= 0.0 {
+            break;
+        }
+
+        // The labels that the output left get their share, the others continue
+        let still_active = consistent_with(&text);
+        let mut continuing = 0.0;
+        for (label, step) in step.iter().enumerate() {
+            if !active[label] {
+                continue;
+            }
+            if still_active[label] {
+                continuing += step;
+            } else {
+                weights[label] += mass * step / total;
+                active[label] = false;
+            }
+        }
+        mass *= continuing / total;
+        analyzed = true;
+        text_before = text;
+    }
+
+    let remaining = active.iter().filter(|active| **active).count();
+    if analyzed && remaining > 0 {
+        for (weight, active) in weights.iter_mut().zip(&active) {
+            if *active {
+                *weight += mass / remaining as f64;
+            }
+        }
+        weights
+    } else {
+        // Without log probabilities, all that is known is the chosen label
+        labels
+            .iter()
+            .map(|label| if label == chosen { 1.0 } else { 0.0 })
+            .collect()
+    }
+}
+
+fn common_prefix_len(strings: &[String]) -> usize {
+    let Some((first, rest)) = strings.split_first() else {
+        return 0;
+    };
+
+    let mut len = first.len();
+    for string in rest {
+        len = first
+            .char_indices()
+            .zip(string.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(string.len()), |((index, _), _)| index.min(len));
+    }
+    len
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_util::serve_in_order;
+
+    #[derive(Debug, PartialEq, Deserialize, JsonSchema)]
+    enum Sentiment {
+        Positive,
+        Negative,
+        Neutral,
+    }
+
+    fn token(token: &str, probability: f64) -> Value {
+        json!({ "token": token, "logprob": probability.ln(), "bytes": token.as_bytes() })
+    }
+
+    #[test]
+    fn labels_are_collected_from_the_schema() {
+        let mut labels = Vec::new();
+        collect_labels(
+            JsonStructure::new::<Sentiment>().schema().as_value(),
+            &mut labels,
+        );
+        assert_eq!(labels, ["Positive", "Negative", "Neutral"]);
+        assert_eq!(
+            common_prefix_len(&["\"Negative\"".to_string(), "\"Neutral\"".to_string()]),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn probabilities_come_from_the_first_distinguishing_token() {
+        let mut distinguishing = token("Ne", 0.7);
+        distinguishing["top_logprobs"] = json!([
+            token("Ne", 0.7),
+            token("Pos", 0.2),
+            token("N", 0.05),
+            token("Hello", 0.05),
+        ]);
+        let mut second = token("gative", 0.6);
+        second["top_logprobs"] = json!([token("gative", 0.6), token("utral", 0.4)]);
+
+        let (ollama, _) = serve_in_order(vec![json!({
+            "model": "test",
+            "created_at": "2026-01-01T00:00:00Z",
+            "response": "\"Negative\"",
+            "done": true,
+            "logprobs": [token("\"", 1.0), distinguishing, second, token("\"", 1.0)],
+        })])
+        .await;
+
+        let classification = ollama
+            .classify::<Sentiment>("test".to_string(), "This is awful")
+            .await
+            .unwrap();
+        assert_eq!(classification.label, Sentiment::Negative);
+
+        // "Ne" and "N" are shared by Negative and Neutral, which the next token splits, and
+        // "Hello" matches no label
+        let expected = [
+            (Sentiment::Negative, 0.75 / 0.95 * 0.6),
+            (Sentiment::Neutral, 0.75 / 0.95 * 0.4),
+            (Sentiment::Positive, 0.2 / 0.95),
+        ];
+        for ((label, probability), (expected_label, expected_probability)) in
+            classification.probabilities.iter().zip(expected)
+        {
+            assert_eq!(*label, expected_label);
+            assert!((probability - expected_probability).abs() < 1e-9);
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{error::OllamaError, generation::parameters::LogprobsData, Ollama};
+use crate::{
+    error::OllamaError,
+    generation::parameters::{DoneReason, LogprobsData},
+    Ollama,
+};
 
 use request::GenerationRequest;
 
@@ -36,6 +40,8 @@ impl Ollama {
 
         use crate::error::OllamaError;
 
+        let request = self.apply_defaults(request);
+
         let url = format!("{}api/generate", self.url_str());
         let builder = self.reqwest_client.post(url);
 
@@ -72,10 +78,74 @@ impl Ollama {
 
     /// Completion generation with a single response.
     /// Returns a single `GenerationResponse` object
+    ///
+    /// With [`GenerationRequest::auto_continue`], a response that is cut off is continued by
+    /// follow-up requests, and the returned response holds the whole output. Continuing needs
+    /// the context and the log probabilities of the response, which are requested for it: if
+    /// the server doesn't return them, an error is returned rather than the cut-off response.
+    ///
+    /// An empty prompt only loads the model, so the last tokens of the output are removed from
+    /// the context and sent back as a raw prompt, rebuilt from their bytes. The model tokenizes
+    /// that text again, which usually, but not always, gives back the same tokens. A character
+    /// that is cut in the middle is dropped and generated again.
     pub async fn generate(
+        &self,
+        mut request: GenerationRequest<'_>,
+    ) -> crate::error::Result<GenerationResponse> {
+        let max_continuations = request.auto_continue.unwrap_or(0);
+        let logprobs_requested = request.logprobs == Some(true);
+        if max_continuations > 0 {
+            request.logprobs = Some(true);
+        }
+        let mut response = self.generate_once(request.clone()).await?;
+
+        let mut continuations = 0;
+        while response.done_reason == Some(DoneReason::Length) && continuations < max_continuations
+        {
+            let (Some(mut context), Some(tail)) = (
+                response.context.clone(),
+                response.logprobs.as_deref().and_then(TokenTail::new),
+            ) else {
+                return Err(OllamaError::Other(
+                    "Can't continue the cut-off response: it has no context or logprobs"
+                        .to_string(),
+                ));
+            };
+            let Some(kept) = context.0.len().checked_sub(tail.tokens) else {
+                return Err(OllamaError::Other(
+                    "Can't continue the cut-off response: its context is too short".to_string(),
+                ));
+            };
+            context.0.truncate(kept);
+            if tail.cut_character {
+                // The server may have sent the partial character as replacement characters
+                let trimmed = response.response.trim_end_matches('\u{FFFD}').len();
+                response.response.truncate(trimmed);
+            }
+
+            let mut follow_up = request.clone();
+            follow_up.prompt = tail.text.into();
+            follow_up.context = Some(context);
+            follow_up.raw = Some(true);
+            follow_up.images = Vec::new();
+            let continuation = self.generate_once(follow_up).await?;
+
+            response.append(continuation);
+            continuations += 1;
+        }
+
+        if !logprobs_requested {
+            response.logprobs = None;
+        }
+        Ok(response)
+    }
+
+    async fn generate_once(
         &self,
         request: GenerationRequest<'_>,
     ) -> crate::error::Result<GenerationResponse> {
+        let request = self.apply_defaults(request);
+
         let url = format!("{}api/generate", self.url_str());
         let builder = self.reqwest_client.post(url);
 
@@ -103,6 +173,82 @@ impl Ollama {
     }
 }
 
+/// The last tokens of a cut-off output, to send back as a raw prompt.
+struct TokenTail {
+    /// The number of tokens to remove from the context
+    tokens: usize,
+    text: String,
+    /// Whether the output ends in the middle of a character, which is left out of `text`
+    cut_character: bool,
+}
+
+impl TokenTail {
+    /// Takes the fewest last tokens whose bytes start at a character boundary and hold at least
+    /// one whole character. A single token may be part of a multi-byte character.
+    fn new(logprobs: &[LogprobsData]) -> Option<Self> {
+        let mut bytes = Vec::new();
+        for (index, logprob) in logprobs.iter().rev().enumerate() {
+            let token_bytes = if logprob.bytes.is_empty() {
+                logprob.token.as_bytes().to_vec()
+            } else {
+                logprob.bytes.iter().map(|&byte| byte as u8).collect()
+            };
+            bytes.splice(0..0, token_bytes);
+
+            let (valid, cut_character) = match std::str::from_utf8(&bytes) {
+                Ok(text) => (text.len(), false),
+                // Only the end is incomplete
+                Err(err) if err.error_len().is_none() => (err.valid_up_to(), true),
+                Err(_) => continue,
+            };
+            if valid > 0 {
+                bytes.truncate(valid);
+                return Some(Self {
+                    tokens: index + 1,
+                    text: String::from_utf8(bytes).ok()?,
+                    cut_character,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+impl GenerationResponse {
+    /// Appends the output of a continuation of this response.
+    fn append(&mut self, continuation: GenerationResponse) {
+        fn sum(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            }
+        }
+
+        self.response.push_str(&continuation.response);
+        if let Some(thinking) = continuation.thinking {
+            self.thinking
+                .get_or_insert_with(String::new)
+                .push_str(&thinking);
+        }
+        if let Some(logprobs) = continuation.logprobs {
+            self.logprobs.get_or_insert_with(Vec::new).extend(logprobs);
+        }
+
+        self.total_duration = sum(self.total_duration, continuation.total_duration);
+        self.load_duration = sum(self.load_duration, continuation.load_duration);
+        self.prompt_eval_count = sum(self.prompt_eval_count, continuation.prompt_eval_count);
+        self.prompt_eval_duration =
+            sum(self.prompt_eval_duration, continuation.prompt_eval_duration);
+        self.eval_count = sum(self.eval_count, continuation.eval_count);
+        self.eval_duration = sum(self.eval_duration, continuation.eval_duration);
+        self.created_at = continuation.created_at;
+        self.context = continuation.context;
+        self.done = continuation.done;
+        self.done_reason = continuation.done_reason;
+    }
+}
+
 /// An encoding of a conversation returned by Ollama after a completion request, this can be sent in a new request to keep a conversational memory.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenerationContext(pub Vec<i32>);
@@ -117,6 +263,9 @@ pub struct GenerationResponse {
     pub response: String,
     /// Whether the completion is done. If the completion is streaming, this will be false until the last response.
     pub done: bool,
+    /// Why the model stopped generating. This is only present if the completion is done.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done_reason: Option<DoneReason>,
     /// An encoding of the conversation used in this response, this can be sent in the next request to keep a conversational memory
     pub context: Option<GenerationContext>,
     /// Time spent generating the response
@@ -136,3 +285,150 @@ pub struct GenerationResponse {
     /// The log probabilities (only if `logprobs` is set to `true`)
     pub logprobs: Option<Vec<LogprobsData>>,
 }
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::test_util::serve_in_order;
+
+    fn token(token: &str) -> Value {
+        json!({ "token": token, "logprob": -0.1, "bytes": token.as_bytes() })
+    }
+
+    #[tokio::test]
+    async fn cut_off_responses_are_continued() {
+        let (ollama, mut requests) = serve_in_order(vec![
+            json!({
+                "model": "test",
+                "created_at": "2026-01-01T00:00:00Z",
+                "response": "Once upon",
+                "done": true,
+                "done_reason": "length",
+                "context": [1, 2, 3, 4],
+                "eval_count": 2,
+                "logprobs": [token("Once"), token(" upon")],
+            }),
+            json!({
+                "model": "test",
+                "created_at": "2026-01-01T00:00:01Z",
+                "response": " a time.",
+                "done": true,
+                "done_reason": "stop",
+                "context": [1, 2, 3, 4, 5, 6, 7],
+                "eval_count": 3,
+                "logprobs": [token(" a"), token(" time"), token(".")],
+            }),
+        ])
+        .await;
+
+        let response = ollama
+            .generate(
+                GenerationRequest::new("test".to_string(), "Tell me a story").auto_continue(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.response, "Once upon a time.");
+        assert_eq!(response.done_reason, Some(DoneReason::Stop));
+        assert_eq!(response.eval_count, Some(5));
+        // The logprobs needed to continue weren't requested by the caller
+        assert!(response.logprobs.is_none());
+        assert_eq!(response.context.unwrap().0, [1, 2, 3, 4, 5, 6, 7]);
+
+        let first = requests.recv().await.unwrap().body;
+        assert_eq!(first["logprobs"], true);
+        let follow_up = requests.recv().await.unwrap().body;
+        assert_eq!(follow_up["prompt"], " upon");
+        assert_eq!(follow_up["context"], json!([1, 2, 3]));
+        assert_eq!(follow_up["raw"], true);
+    }
+
+    #[tokio::test]
+    async fn multi_byte_tokens_are_continued_from_their_bytes() {
+        // `é` is split in two tokens, whose text can't be sent back as is
+        let half = |byte: u8| json!({ "token": "\u{FFFD}", "logprob": -0.1, "bytes": [byte] });
+        let (ollama, mut requests) = serve_in_order(vec![
+            json!({
+                "model": "test",
+                "created_at": "2026-01-01T00:00:00Z",
+                "response": "Caf\u{FFFD}",
+                "done": true,
+                "done_reason": "length",
+                "context": [1, 2, 3, 4],
+                "logprobs": [token("Caf"), half(0xC3)],
+            }),
+            json!({
+                "model": "test",
+                "created_at": "2026-01-01T00:00:01Z",
+                "response": "é au lait, caf",
+                "done": true,
+                "done_reason": "length",
+                "context": [1, 2, 3, 5, 6, 7, 8],
+                "logprobs": [half(0xC3), half(0xA9), token(" au lait, caf")],
+            }),
+            json!({
+                "model": "test",
+                "created_at": "2026-01-01T00:00:02Z",
+                "response": "és.",
+                "done": true,
+                "done_reason": "stop",
+                "context": [1, 2, 3, 5, 6, 7, 8, 9],
+                "logprobs": [token("és.")],
+            }),
+        ])
+        .await;
+
+        let response = ollama
+            .generate(GenerationRequest::new("test".to_string(), "Coffee?").auto_continue(2))
+            .await
+            .unwrap();
+        assert_eq!(response.response, "Café au lait, cafés.");
+
+        requests.recv().await.unwrap();
+        // The cut `é` is dropped with the token before it
+        let follow_up = requests.recv().await.unwrap().body;
+        assert_eq!(follow_up["prompt"], "Caf");
+        assert_eq!(follow_up["context"], json!([1, 2]));
+        // A whole last token is sent back alone
+        let follow_up = requests.recv().await.unwrap().body;
+        assert_eq!(follow_up["prompt"], " au lait, caf");
+        assert_eq!(follow_up["context"], json!([1, 2, 3, 5, 6, 7]));
+    }
+
+    #[tokio::test]
+    async fn responses_without_logprobs_are_not_continued() {
+        let (ollama, _) = serve_in_order(vec![json!({
+            "model": "test",
+            "created_at": "2026-01-01T00:00:00Z",
+            "response": "Once upon",
+            "done": true,
+            "done_reason": "length",
+            "context": [1, 2, 3, 4],
+        })])
+        .await;
+
+        let error = ollama
+            .generate(
+                GenerationRequest::new("test".to_string(), "Tell me a story").auto_continue(2),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cut-off response"), "{error}");
+    }
+
+    #[test]
+    fn done_reasons_roundtrip() {
+        for (reason, expected) in [
+            ("stop", DoneReason::Stop),
+            ("length", DoneReason::Length),
+            ("load", DoneReason::Load),
+            ("unload", DoneReason::Unload),
+            ("timeout", DoneReason::Other("timeout".to_string())),
+        ] {
+            let parsed = serde_json::from_value::<DoneReason>(json!(reason)).unwrap();
+            assert_eq!(parsed, expected);
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), json!(reason));
+        }
+    }
+}
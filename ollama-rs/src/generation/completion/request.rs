@@ -42,12 +42,16 @@ pub struct GenerationRequest<'a> {
     pub logprobs: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_logprobs: Option<u32>,
+    /// The maximum number of follow-up requests when the response is cut off, see
+    /// [`GenerationRequest::auto_continue`].
+    #[serde(skip)]
+    pub auto_continue: Option<u32>,
 }
 
 impl<'a> GenerationRequest<'a> {
-    pub fn new(model_name: String, prompt: impl Into<Cow<'a, str>>) -> Self {
+    pub fn new(model_name: impl Into<String>, prompt: impl Into<Cow<'a, str>>) -> Self {
         Self {
-            model_name,
+            model_name: model_name.into(),
             prompt: prompt.into(),
             suffix: None,
             images: Vec::new(),
@@ -61,11 +65,12 @@ impl<'a> GenerationRequest<'a> {
             think: None,
             logprobs: None,
             top_logprobs: None,
+            auto_continue: None,
         }
     }
 
     /// Creates a new generation request with an suffix. Useful for code completion requests
-    pub fn new_with_suffix(model_name: String, prompt: String, suffix: String) -> Self {
+    pub fn new_with_suffix(model_name: impl Into<String>, prompt: String, suffix: String) -> Self {
         let out = Self::new(model_name, prompt);
         out.suffix(suffix)
     }
@@ -147,4 +152,15 @@ impl<'a> GenerationRequest<'a> {
         self.top_logprobs = Some(top_logprobs);
         self
     }
+
+    /// Continues the response when it is cut off by the `num_predict` limit or the context
+    /// length, with up to `max_continuations` follow-up requests whose output is appended to the
+    /// response. This only applies to [`Ollama::generate`](crate::Ollama::generate).
+    ///
+    /// The follow-up requests are made in raw mode from the context of the previous response,
+    /// whose last token is resent as the prompt. `logprobs` is enabled to know that token.
+    pub fn auto_continue(mut self, max_continuations: u32) -> Self {
+        self.auto_continue = Some(max_continuations);
+        self
+    }
 }
@@ -0,0 +1,259 @@
+//! Default request parameters of a client, set with [`OllamaBuilder::defaults`] and
+//! [`OllamaBuilder::model_defaults`].
+//!
+//! [`OllamaBuilder::defaults`]: crate::OllamaBuilder::defaults
+//! [`OllamaBuilder::model_defaults`]: crate::OllamaBuilder::model_defaults
+
+use crate::{
+    generation::{
+        chat::request::ChatMessageRequest,
+        completion::request::GenerationRequest,
+        embeddings::request::GenerateEmbeddingsRequest,
+        parameters::{KeepAlive, ThinkType},
+    },
+    models::{name::ModelName, ModelOptions},
+    Ollama,
+};
+
+/// Parameters applied to the requests that don't set them.
+///
+/// Defaults are layered: the defaults of the client, then the defaults of the model of the
+/// request, then the request itself. Each layer wins over the previous ones, and options are
+/// merged field by field with [`ModelOptions::merge`].
+///
+/// ```ignore
+/// let ollama = Ollama::builder()
+///     .defaults(RequestDefaults::new().options(ModelOptions::default().temperature(0.2)))
+///     .model_defaults(
+///         "qwen3",
+///         RequestDefaults::new()
+///             .options(ModelOptions::default().num_ctx(16384))
+///             .think(ThinkType::False),
+///     )
+///     .build();
+///
+/// // Sent with a temperature of 0.5 and a context of 16384 tokens, without thinking
+/// let request = ChatMessageRequest::new("qwen3".to_string(), messages)
+///     .options(ModelOptions::default().temperature(0.5));
+/// println!("{:?}", ollama.apply_defaults(request).options);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestDefaults {
+    pub options: Option<ModelOptions>,
+    pub keep_alive: Option<KeepAlive>,
+    /// Not applied to embeddings requests, which can't think.
+    pub think: Option<ThinkType>,
+}
+
+impl RequestDefaults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn options(mut self, options: ModelOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn keep_alive(mut self, keep_alive: KeepAlive) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    pub fn think(mut self, think: ThinkType) -> Self {
+        self.think = Some(think);
+        self
+    }
+
+    /// Merges `overrides` over these defaults: the parameters set in `overrides` win, and
+    /// options are merged field by field.
+    pub fn merge(&self, overrides: &RequestDefaults) -> RequestDefaults {
+        RequestDefaults {
+            options: merge_options(self.options.as_ref(), overrides.options.as_ref()),
+            keep_alive: overrides
+                .keep_alive
+                .clone()
+                .or_else(|| self.keep_alive.clone()),
+            think: overrides.think.clone().or_else(|| self.think.clone()),
+        }
+    }
+}
+
+fn merge_options(
+    defaults: Option<&ModelOptions>,
+    overrides: Option<&ModelOptions>,
+) -> Option<ModelOptions> {
+    match (defaults, overrides) {
+        (Some(defaults), Some(overrides)) => Some(defaults.merge(overrides)),
+        (defaults, overrides) => overrides.or(defaults).cloned(),
+    }
+}
+
+/// A request that [`RequestDefaults`] can be applied to.
+pub trait ApplyDefaults {
+    /// The name of the model of the request, which selects the model defaults.
+    fn model_name(&self) -> &str;
+
+    /// Sets the parameters of the request that aren't set from `defaults`.
+    fn apply_defaults(&mut self, defaults: &RequestDefaults);
+}
+
+impl ApplyDefaults for ChatMessageRequest {
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn apply_defaults(&mut self, defaults: &RequestDefaults) {
+        self.options = merge_options(defaults.options.as_ref(), self.options.as_ref());
+        self.keep_alive = self
+            .keep_alive
+            .take()
+            .or_else(|| defaults.keep_alive.clone());
+        self.think = self.think.take().or_else(|| defaults.think.clone());
+    }
+}
+
+impl ApplyDefaults for GenerationRequest<'_> {
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn apply_defaults(&mut self, defaults: &RequestDefaults) {
+        self.options = merge_options(defaults.options.as_ref(), self.options.as_ref());
+        self.keep_alive = self
+            .keep_alive
+            .take()
+            .or_else(|| defaults.keep_alive.clone());
+        self.think = self.think.take().or_else(|| defaults.think.clone());
+    }
+}
+
+impl ApplyDefaults for GenerateEmbeddingsRequest {
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn apply_defaults(&mut self, defaults: &RequestDefaults) {
+        self.options = merge_options(defaults.options.as_ref(), self.options.as_ref());
+        self.keep_alive = self
+            .keep_alive
+            .take()
+            .or_else(|| defaults.keep_alive.clone());
+    }
+}
+
+/// The key of the defaults of a model: its parsed name, or the name as is if it can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum ModelKey {
+    Name(ModelName),
+    Raw(String),
+}
+
+impl ModelKey {
+    pub(crate) fn new(model: &str) -> Self {
+        match ModelName::parse(model) {
+            Ok(name) => Self::Name(name),
+            Err(_) => Self::Raw(model.to_string()),
+        }
+    }
+}
+
+impl Ollama {
+    /// The defaults of the client merged with the defaults of `model`.
+    ///
+    /// Model names are compared as [`ModelName`]s, so `llama3` matches the defaults of
+    /// `llama3:latest`, and the other way around. Names that can't be parsed only match the same
+    /// name.
+    pub fn defaults_for(&self, model: &str) -> RequestDefaults {
+        match self.model_defaults.get(&ModelKey::new(model)) {
+            Some(model_defaults) => self.defaults.merge(model_defaults),
+            None => self.defaults.clone(),
+        }
+    }
+
+    /// Returns `request` with the defaults of the client and of its model applied, as it is
+    /// sent by the request methods.
+    pub fn apply_defaults<R: ApplyDefaults>(&self, mut request: R) -> R {
+        let defaults = self.defaults_for(request.model_name());
+        request.apply_defaults(&defaults);
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::chat::ChatMessage;
+
+    #[test]
+    fn request_values_win_over_model_and_client_defaults() {
+        let ollama = Ollama::builder()
+            .defaults(
+                RequestDefaults::new()
+                    .options(ModelOptions::default().temperature(0.2).seed(1))
+                    .keep_alive(KeepAlive::Indefinitely),
+            )
+            .model_defaults(
+                "qwen3:latest",
+                RequestDefaults::new()
+                    .options(ModelOptions::default().num_ctx(16384).seed(2))
+                    .think(ThinkType::False),
+            )
+            .build();
+
+        let request = ChatMessageRequest::new(
+            "qwen3".to_string(),
+            vec![ChatMessage::user("Hello".to_string())],
+        )
+        .options(ModelOptions::default().temperature(0.5));
+        let request = ollama.apply_defaults(request);
+
+        assert_eq!(
+            request.options,
+            Some(
+                ModelOptions::default()
+                    .temperature(0.5)
+                    .seed(2)
+                    .num_ctx(16384)
+            )
+        );
+        assert_eq!(request.keep_alive, Some(KeepAlive::Indefinitely));
+        assert_eq!(request.think, Some(ThinkType::False));
+
+        // Other models only get the client defaults
+        let request = ollama.apply_defaults(GenerateEmbeddingsRequest::new(
+            "nomic-embed-text".to_string(),
+            "Hello".into(),
+        ));
+        assert_eq!(
+            request.options,
+            Some(ModelOptions::default().temperature(0.2).seed(1))
+        );
+    }
+
+    #[test]
+    fn equivalent_model_names_share_their_defaults() {
+        let ollama = Ollama::builder()
+            .model_defaults("qwen3", RequestDefaults::new().think(ThinkType::True))
+            .model_defaults(
+                "qwen3:latest",
+                RequestDefaults::new().think(ThinkType::False),
+            )
+            .model_defaults("not a name", RequestDefaults::new().think(ThinkType::True))
+            .build();
+
+        // The last defaults of equivalent names win
+        assert_eq!(ollama.defaults_for("qwen3").think, Some(ThinkType::False));
+        assert_eq!(
+            ollama
+                .defaults_for("registry.ollama.ai/library/qwen3:latest")
+                .think,
+            Some(ThinkType::False)
+        );
+        assert_eq!(
+            ollama.defaults_for("not a name").think,
+            Some(ThinkType::True)
+        );
+        assert_eq!(ollama.defaults_for("llama3").think, None);
+    }
+}
@@ -14,6 +14,8 @@ impl Ollama {
         &self,
         request: GenerateEmbeddingsRequest,
     ) -> crate::error::Result<GenerateEmbeddingsResponse> {
+        let request = self.apply_defaults(request);
+
         let url = format!("{}api/embed", self.url_str());
         let builder = self.reqwest_client.post(url);
 
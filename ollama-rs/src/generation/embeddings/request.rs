@@ -65,9 +65,9 @@ pub struct GenerateEmbeddingsRequest {
 }
 
 impl GenerateEmbeddingsRequest {
-    pub fn new(model_name: String, input: EmbeddingsInput) -> Self {
+    pub fn new(model_name: impl Into<String>, input: EmbeddingsInput) -> Self {
         Self {
-            model_name,
+            model_name: model_name.into(),
             input,
             ..Default::default()
         }
@@ -0,0 +1,240 @@
+//! Analysis of token log probabilities, as returned when `logprobs` is set on a request.
+
+use crate::generation::parameters::LogprobsData;
+
+impl LogprobsData {
+    /// The probability of the token, between 0 and 1.
+    pub fn probability(&self) -> f64 {
+        self.logprob.exp()
+    }
+
+    /// The entropy of the distribution of the top alternatives at this position, in nats, or
+    /// `None` without `top_logprobs`.
+    ///
+    /// Only the top alternatives are known, so their probabilities are normalized to sum to 1:
+    /// this underestimates the entropy when the rest of the distribution isn't negligible.
+    pub fn entropy(&self) -> Option<f64> {
+        if self.top_logprobs.is_empty() {
+            return None;
+        }
+
+        let probabilities = self
+            .top_logprobs
+            .iter()
+            .map(|alternative| alternative.logprob.exp())
+            .collect::<Vec<_>>();
+        let total = probabilities.iter().sum::<f64>();
+        if total <= 0.0 {
+            return None;
+        }
+
+        Some(
+            -probabilities
+                .iter()
+                .map(|probability| probability / total)
+                .filter(|probability| *probability > 0.0)
+                .map(|probability| probability * probability.ln())
+                .sum::<f64>(),
+        )
+    }
+
+    /// The bytes of the token, which may be part of a multi-byte character.
+    fn token_bytes(&self) -> Vec<u8> {
+        if self.bytes.is_empty() {
+            self.token.as_bytes().to_vec()
+        } else {
+            self.bytes.iter().map(|byte| *byte as u8).collect()
+        }
+    }
+}
+
+/// A span of consecutive tokens the model wasn't confident about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfidenceSpan {
+    /// The index of the first token of the span.
+    pub start: usize,
+    /// The index after the last token of the span.
+    pub end: usize,
+    /// The text of the span.
+    pub text: String,
+    /// The probability of the least likely token of the span.
+    pub min_probability: f64,
+}
+
+/// Analysis helpers over the log probabilities of a generated sequence.
+///
+/// ```ignore
+/// let res = ollama
+///     .generate(GenerationRequest::new(model, prompt).logprobs(true).top_logprobs(5))
+///     .await?;
+/// let logprobs = res.logprobs.unwrap_or_default();
+///
+/// println!("Perplexity: {:?}", logprobs.perplexity());
+/// for span in logprobs.low_confidence_spans(0.5) {
+///     println!("Unsure about {:?} ({:.0}%)", span.text, span.min_probability * 100.0);
+/// }
+/// ```
+pub trait LogprobsAnalysis {
+    /// The sum of the log probabilities of the tokens.
+    fn total_logprob(&self) -> f64;
+
+    /// The probability of the whole sequence.
+    ///
+    /// For a short answer, such as a classification label, this is the confidence of the model
+    /// in that answer.
+    fn confidence(&self) -> f64 {
+        self.total_logprob().exp()
+    }
+
+    /// The perplexity of the sequence: the exponential of the mean negative log probability, or
+    /// `None` for an empty sequence. Lower is more confident, 1 is certain.
+    fn perplexity(&self) -> Option<f64>;
+
+    /// The probability of each token.
+    fn token_probabilities(&self) -> Vec<f64>;
+
+    /// The entropy of each position, see [`LogprobsData::entropy`].
+    fn entropies(&self) -> Vec<Option<f64>>;
+
+    /// The spans of consecutive tokens whose probability is below `threshold`, least confident
+    /// first.
+    fn low_confidence_spans(&self, threshold: f64) -> Vec<ConfidenceSpan>;
+
+    /// The text of the sequence, decoded from the bytes of the tokens, so that characters split
+    /// across tokens are reconstructed.
+    fn text(&self) -> String;
+}
+
+impl LogprobsAnalysis for [LogprobsData] {
+    fn total_logprob(&self) -> f64 {
+        self.iter().map(|data| data.logprob).sum()
+    }
+
+    fn perplexity(&self) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+
+        Some((-self.total_logprob() / self.len() as f64).exp())
+    }
+
+    fn token_probabilities(&self) -> Vec<f64> {
+        self.iter().map(LogprobsData::probability).collect()
+    }
+
+    fn entropies(&self) -> Vec<Option<f64>> {
+        self.iter().map(LogprobsData::entropy).collect()
+    }
+
+    fn low_confidence_spans(&self, threshold: f64) -> Vec<ConfidenceSpan> {
+        let mut spans = Vec::new();
+        let mut start = None;
+
+        for index in 0..=self.len() {
+            let below = self
+                .get(index)
+                .is_some_and(|data| data.probability() < threshold);
+
+            match (below, start) {
+                (true, None) => start = Some(index),
+                (false, Some(span_start)) => {
+                    let tokens = &self[span_start..index];
+                    spans.push(ConfidenceSpan {
+                        start: span_start,
+                        end: index,
+                        text: tokens.text(),
+                        min_probability: tokens
+                            .iter()
+                            .map(LogprobsData::probability)
+                            .fold(f64::INFINITY, f64::min),
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        spans.sort_by(|a, b| a.min_probability.total_cmp(&b.min_probability));
+        spans
+    }
+
+    fn text(&self) -> String {
+        let bytes = self
+            .iter()
+            .flat_map(LogprobsData::token_bytes)
+            .collect::<Vec<_>>();
+
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::parameters::TokenLogprob;
+
+    fn token(text: &str, bytes: &[u8], probability: f64) -> LogprobsData {
+        LogprobsData {
+            token: text.to_string(),
+            logprob: probability.ln(),
+            bytes: bytes.iter().map(|byte| *byte as i64).collect(),
+            top_logprobs: Vec::new(),
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn sequence_statistics() {
+        let logprobs = [
+            token("The", b"The", 0.9),
+            token(" sky", b" sky", 0.5),
+            token(" is", b" is", 0.2),
+            token(" blue", b" blue", 0.4),
+        ];
+
+        assert_close(logprobs.confidence(), 0.9 * 0.5 * 0.2 * 0.4);
+        assert_close(
+            logprobs.perplexity().unwrap(),
+            (0.9f64 * 0.5 * 0.2 * 0.4).powf(-0.25),
+        );
+        assert_eq!(logprobs.text(), "The sky is blue");
+        assert_eq!([].perplexity(), None);
+
+        let spans = logprobs.low_confidence_spans(0.6);
+        assert_eq!(spans.len(), 1);
+        assert_eq!((spans[0].start, spans[0].end), (1, 4));
+        assert_eq!(spans[0].text, " sky is blue");
+        assert_close(spans[0].min_probability, 0.2);
+    }
+
+    #[test]
+    fn multi_byte_characters_are_reconstructed() {
+        // "é" is split across two tokens
+        let logprobs = [
+            token("caf", b"caf", 0.9),
+            token("\u{FFFD}", &[0xC3], 0.8),
+            token("\u{FFFD}", &[0xA9], 0.8),
+        ];
+
+        assert_eq!(logprobs.text(), "café");
+    }
+
+    #[test]
+    fn entropy_from_top_logprobs() {
+        let mut data = token("yes", b"yes", 0.5);
+        assert_eq!(data.entropy(), None);
+
+        data.top_logprobs = ["yes", "no"]
+            .into_iter()
+            .map(|token| TokenLogprob {
+                token: token.to_string(),
+                logprob: 0.5f64.ln(),
+                bytes: Vec::new(),
+            })
+            .collect();
+        assert_close(data.entropy().unwrap(), 2f64.ln());
+    }
+}
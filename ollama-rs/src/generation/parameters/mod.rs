@@ -111,6 +111,10 @@ impl JsonStructure {
     pub fn new_for_schema(schema: Schema) -> Self {
         Self { schema }
     }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
 }
 
 impl From<Schema> for JsonStructure {
@@ -282,9 +286,64 @@ impl TimeUnit {
     }
 }
 
+/// Why a model stopped generating.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum DoneReason {
+    /// The model finished its response, or hit a stop sequence.
+    Stop,
+    /// The response hit the `num_predict` limit or the context length, and was cut off.
+    Length,
+    /// The model was loaded, without generating anything.
+    Load,
+    /// The model was unloaded.
+    Unload,
+    /// A reason this version doesn't know about.
+    Other(String),
+}
+
+impl From<String> for DoneReason {
+    fn from(reason: String) -> Self {
+        match reason.as_str() {
+            "stop" => DoneReason::Stop,
+            "length" => DoneReason::Length,
+            "load" => DoneReason::Load,
+            "unload" => DoneReason::Unload,
+            _ => DoneReason::Other(reason),
+        }
+    }
+}
+
+impl From<DoneReason> for String {
+    fn from(reason: DoneReason) -> Self {
+        match reason {
+            DoneReason::Stop => "stop".to_string(),
+            DoneReason::Length => "length".to_string(),
+            DoneReason::Load => "load".to_string(),
+            DoneReason::Unload => "unload".to_string(),
+            DoneReason::Other(reason) => reason,
+        }
+    }
+}
+
+/// The log probability of a generated token. See [`LogprobsAnalysis`](crate::generation::logprobs::LogprobsAnalysis)
+/// to analyze a sequence of them.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LogprobsData {
     pub token: String,
     pub logprob: f64,
+    #[serde(default)]
+    pub bytes: Vec<i64>,
+    /// The most likely tokens at this position (only if `top_logprobs` is set)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_logprobs: Vec<TokenLogprob>,
+}
+
+/// An alternative token at a position, with its log probability.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    #[serde(default)]
     pub bytes: Vec<i64>,
 }
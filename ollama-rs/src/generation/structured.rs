@@ -0,0 +1,333 @@
+//! Chat and completion generation straight into a Rust type.
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    error::OllamaError,
+    generation::{
+        chat::{request::ChatMessageRequest, ChatMessage, ChatMessageResponse},
+        completion::{request::GenerationRequest, GenerationResponse},
+        parameters::{FormatType, JsonStructure},
+    },
+    Ollama,
+};
+
+pub mod partial;
+pub mod repair;
+pub mod validate;
+
+pub use partial::PartialJsonParser;
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+#[cfg(feature = "stream")]
+pub use partial::{partial_json_stream, PartialJsonStream};
+pub use repair::repair_json;
+pub use validate::SchemaViolation;
+
+/// A response parsed into `T`, with the raw response it was parsed from.
+#[derive(Debug, Clone)]
+pub struct StructuredResponse<T, R> {
+    pub value: T,
+    pub response: R,
+}
+
+/// Options for structured generation.
+#[derive(Debug, Clone, Copy)]
+pub struct StructuredOptions {
+    max_retries: usize,
+    repair: bool,
+}
+
+impl Default for StructuredOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            repair: false,
+        }
+    }
+}
+
+impl StructuredOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many times the request is retried when the response is invalid. (Default: 2)
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Repairs common problems in responses, such as code fences, trailing commas and truncated
+    /// output, before they are validated. See [`repair_json`]. (Default: false)
+    pub fn repair(mut self, repair: bool) -> Self {
+        self.repair = repair;
+        self
+    }
+}
+
+/// Why a response is invalid.
+enum Invalid {
+    Json(serde_json::Error),
+    Schema(Vec<SchemaViolation>),
+}
+
+impl Invalid {
+    /// A message asking the model to correct its response.
+    fn correction(&self) -> String {
+        match self {
+            Invalid::Json(err) => format!(
+                "Your response could not be parsed: {err}. Answer again with only JSON matching the requested schema."
+            ),
+            Invalid::Schema(violations) => {
+                let mut correction =
+                    "Your response doesn't match the requested schema:\n".to_string();
+                for violation in violations {
+                    correction.push_str(&format!("- {violation}\n"));
+                }
+                correction.push_str("Answer again with only JSON matching the requested schema.");
+                correction
+            }
+        }
+    }
+
+    fn into_error(self, content: String) -> OllamaError {
+        match self {
+            Invalid::Json(source) => OllamaError::StructuredOutputError { content, source },
+            Invalid::Schema(violations) => OllamaError::SchemaViolationError {
+                content,
+                violations,
+            },
+        }
+    }
+}
+
+/// Parses `content` into a `T`, after validating it against the schema of `T`.
+fn parse<T: DeserializeOwned>(
+    structure: &JsonStructure,
+    content: &str,
+    options: StructuredOptions,
+) -> Result<T, Invalid> {
+    let value = if options.repair {
+        serde_json::from_str::<Value>(&repair_json(content))
+    } else {
+        serde_json::from_str::<Value>(content)
+    }
+    .map_err(Invalid::Json)?;
+
+    structure.validate(&value).map_err(Invalid::Schema)?;
+    serde_json::from_value(value).map_err(Invalid::Json)
+}
+
+impl Ollama {
+    /// Chat message generation into a `T`.
+    ///
+    /// The format of the request is set to the JSON schema of `T`, and the response is validated
+    /// against it. If the response is invalid, the error is sent back to the model, asking it to
+    /// correct its response, up to 2 times.
+    pub async fn send_chat_messages_structured<T: JsonSchema + DeserializeOwned>(
+        &self,
+        request: ChatMessageRequest,
+    ) -> crate::error::Result<StructuredResponse<T, ChatMessageResponse>> {
+        self.send_chat_messages_structured_with_options(request, StructuredOptions::default())
+            .await
+    }
+
+    /// Same as [`Ollama::send_chat_messages_structured`], with the given options.
+    pub async fn send_chat_messages_structured_with_options<T: JsonSchema + DeserializeOwned>(
+        &self,
+        mut request: ChatMessageRequest,
+        options: StructuredOptions,
+    ) -> crate::error::Result<StructuredResponse<T, ChatMessageResponse>> {
+        let structure = JsonStructure::new::<T>();
+        request.format = Some(FormatType::StructuredJson(Box::new(structure.clone())));
+
+        let mut retries = 0;
+        loop {
+            let response = self.send_chat_messages(request.clone()).await?;
+
+            match parse(&structure, &response.message.content, options) {
+                Ok(value) => return Ok(StructuredResponse { value, response }),
+                Err(invalid) if retries == options.max_retries => {
+                    return Err(invalid.into_error(response.message.content))
+                }
+                Err(invalid) => {
+                    request.messages.push(response.message);
+                    request
+                        .messages
+                        .push(ChatMessage::user(invalid.correction()));
+                    retries += 1;
+                }
+            }
+        }
+    }
+
+    /// Completion generation into a `T`.
+    ///
+    /// The format of the request is set to the JSON schema of `T`, and the response is validated
+    /// against it. If the response is invalid, the error is sent back to the model in the
+    /// context of the response, asking it to correct it, up to 2 times.
+    pub async fn generate_structured<T: JsonSchema + DeserializeOwned>(
+        &self,
+        request: GenerationRequest<'_>,
+    ) -> crate::error::Result<StructuredResponse<T, GenerationResponse>> {
+        self.generate_structured_with_options(request, StructuredOptions::default())
+            .await
+    }
+
+    /// Same as [`Ollama::generate_structured`], with the given options.
+    pub async fn generate_structured_with_options<T: JsonSchema + DeserializeOwned>(
+        &self,
+        mut request: GenerationRequest<'_>,
+        options: StructuredOptions,
+    ) -> crate::error::Result<StructuredResponse<T, GenerationResponse>> {
+        let structure = JsonStructure::new::<T>();
+        request.format = Some(FormatType::StructuredJson(Box::new(structure.clone())));
+
+        let mut retries = 0;
+        loop {
+            let response = self.generate(request.clone()).await?;
+
+            match parse(&structure, &response.response, options) {
+                Ok(value) => return Ok(StructuredResponse { value, response }),
+                Err(invalid) if retries == options.max_retries => {
+                    return Err(invalid.into_error(response.response))
+                }
+                Err(invalid) => {
+                    // Without a context, the correction is sent along with the original prompt
+                    match response.context {
+                        Some(context) => {
+                            request.context = Some(context);
+                            request.prompt = invalid.correction().into();
+                        }
+                        None => {
+                            request.prompt = format!(
+                                "{}\n\nYour previous response was:\n{}\n\n{}",
+                                request.prompt,
+                                response.response,
+                                invalid.correction()
+                            )
+                            .into();
+                        }
+                    }
+                    retries += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::test_util::serve_in_order;
+
+    fn chat_response(content: &str) -> Value {
+        serde_json::json!({
+            "model": "test",
+            "created_at": "2026-01-01T00:00:00Z",
+            "message": { "role": "assistant", "content": content },
+            "done": true,
+        })
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, JsonSchema)]
+    struct Country {
+        name: String,
+        capital: String,
+    }
+
+    #[tokio::test]
+    async fn chat_retries_with_a_correction() {
+        let (ollama, mut requests) = serve_in_order(vec![
+            chat_response(r#"{"name": "Canada"}"#),
+            chat_response(r#"{"name": "Canada", "capital": "Ottawa"}"#),
+        ])
+        .await;
+
+        let response = ollama
+            .send_chat_messages_structured::<Country>(ChatMessageRequest::new(
+                "test".to_string(),
+                vec![ChatMessage::user("Tell me about Canada".to_string())],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.value,
+            Country {
+                name: "Canada".to_string(),
+                capital: "Ottawa".to_string(),
+            }
+        );
+        assert_eq!(
+            response.response.message.content,
+            r#"{"name": "Canada", "capital": "Ottawa"}"#
+        );
+
+        let first = requests.recv().await.unwrap().body;
+        assert_eq!(
+            first["format"]["required"],
+            serde_json::json!(["name", "capital"])
+        );
+        let second = requests.recv().await.unwrap().body;
+        let messages = second["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert!(messages[2]["content"]
+            .as_str()
+            .unwrap()
+            .contains("- missing required property `capital`"));
+    }
+
+    #[tokio::test]
+    async fn generate_fails_once_retries_are_exhausted() {
+        let response = serde_json::json!({
+            "model": "test",
+            "created_at": "2026-01-01T00:00:00Z",
+            "response": "not JSON",
+            "done": true,
+            "context": [1, 2, 3],
+        });
+        let (ollama, mut requests) = serve_in_order(vec![response.clone(), response]).await;
+
+        let result = ollama
+            .generate_structured_with_options::<Country>(
+                GenerationRequest::new("test".to_string(), "Tell me about Canada"),
+                StructuredOptions::new().max_retries(1),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(OllamaError::StructuredOutputError { content, .. }) if content == "not JSON"
+        ));
+
+        requests.recv().await.unwrap();
+        let retry = requests.recv().await.unwrap().body;
+        assert_eq!(retry["context"], serde_json::json!([1, 2, 3]));
+        assert!(retry["prompt"]
+            .as_str()
+            .unwrap()
+            .starts_with("Your response could not be parsed"));
+    }
+
+    #[tokio::test]
+    async fn repaired_responses_are_accepted() {
+        let (ollama, _requests) = serve_in_order(vec![chat_response(
+            "```json\n{\"name\": \"Canada\", \"capital\": \"Ottawa\",}\n```",
+        )])
+        .await;
+
+        let response = ollama
+            .send_chat_messages_structured_with_options::<Country>(
+                ChatMessageRequest::new("test".to_string(), vec![]),
+                StructuredOptions::new().max_retries(0).repair(true),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.value.capital, "Ottawa");
+    }
+}
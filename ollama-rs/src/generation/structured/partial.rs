@@ -0,0 +1,162 @@
+//! Incremental parsing of streamed structured output.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::repair_json;
+
+/// Parses JSON as it is streamed, into progressively more complete snapshots.
+///
+/// Each snapshot is the content received so far, completed with [`repair_json`]: open strings,
+/// arrays and objects are closed, and a property is only included once its value has started.
+/// Snapshots can also be deserialized into a type whose fields are all optional, such as:
+///
+/// ```ignore
+/// #[derive(Deserialize, Debug)]
+/// struct PartialCountry {
+///     name: Option<String>,
+///     #[serde(default)]
+///     languages: Vec<String>,
+/// }
+///
+/// let mut parser = PartialJsonParser::new();
+/// while let Some(Ok(response)) = stream.next().await {
+///     if parser.push(&response.message.content).is_some() {
+///         if let Some(country) = parser.parse::<PartialCountry>() {
+///             println!("{country:?}");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PartialJsonParser {
+    content: String,
+    snapshot: Option<Value>,
+}
+
+impl PartialJsonParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a content delta, and returns the new snapshot if it changed.
+    pub fn push(&mut self, delta: &str) -> Option<&Value> {
+        if delta.is_empty() {
+            return None;
+        }
+        self.content.push_str(delta);
+
+        let snapshot = serde_json::from_str::<Value>(&repair_json(&self.content)).ok()?;
+        if self.snapshot.as_ref() == Some(&snapshot) {
+            return None;
+        }
+
+        self.snapshot = Some(snapshot);
+        self.snapshot.as_ref()
+    }
+
+    /// Returns the latest snapshot, or `None` if no value has started yet.
+    pub fn snapshot(&self) -> Option<&Value> {
+        self.snapshot.as_ref()
+    }
+
+    /// Deserializes the latest snapshot into a `T`, or returns `None` if it doesn't fit yet.
+    pub fn parse<T: DeserializeOwned>(&self) -> Option<T> {
+        T::deserialize(self.snapshot.as_ref()?).ok()
+    }
+
+    /// Returns the content received so far.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+#[cfg(feature = "stream")]
+/// A stream of partial values, see [`partial_json_stream`].
+pub type PartialJsonStream<T> =
+    std::pin::Pin<Box<dyn tokio_stream::Stream<Item = Result<T, ()>> + Send>>;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+#[cfg(feature = "stream")]
+/// Turns a chat stream with a JSON format into a stream of progressively more complete `T`s.
+///
+/// A value is yielded each time the snapshot of the content changes and can be deserialized
+/// into a `T`. Use `serde_json::Value`, or a type whose fields are all optional, to get every
+/// snapshot.
+pub fn partial_json_stream<T: DeserializeOwned + Send + 'static>(
+    mut stream: crate::generation::chat::ChatMessageResponseStream,
+) -> PartialJsonStream<T> {
+    use tokio_stream::StreamExt;
+
+    Box::pin(async_stream::stream! {
+        let mut parser = PartialJsonParser::new();
+
+        while let Some(response) = stream.next().await {
+            match response {
+                Ok(response) => {
+                    if parser.push(&response.message.content).is_some() {
+                        if let Some(value) = parser.parse::<T>() {
+                            yield Ok(value);
+                        }
+                    }
+                }
+                Err(()) => {
+                    yield Err(());
+                    break;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct PartialCountry {
+        name: Option<String>,
+        #[serde(default)]
+        languages: Vec<String>,
+    }
+
+    #[test]
+    fn snapshots_grow_with_the_content() {
+        let deltas = [
+            "```json\n{\"na",
+            "me\": \"Can",
+            "ada\", \"languages\"",
+            ": [\"English\", ",
+            "\"French\"]}",
+            "\n```",
+        ];
+        let mut parser = PartialJsonParser::new();
+
+        let snapshots = deltas
+            .into_iter()
+            .map(|delta| parser.push(delta).cloned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            snapshots,
+            [
+                Some(json!({})),
+                Some(json!({ "name": "Can" })),
+                Some(json!({ "name": "Canada" })),
+                Some(json!({ "name": "Canada", "languages": ["English"] })),
+                Some(json!({ "name": "Canada", "languages": ["English", "French"] })),
+                None,
+            ]
+        );
+        assert_eq!(
+            parser.parse::<PartialCountry>(),
+            Some(PartialCountry {
+                name: Some("Canada".to_string()),
+                languages: vec!["English".to_string(), "French".to_string()],
+            })
+        );
+    }
+}
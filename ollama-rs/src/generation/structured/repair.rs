@@ -0,0 +1,190 @@
+//! Repair of common problems in JSON written by models.
+
+/// Repairs common problems in JSON written by models, on a best-effort basis:
+///
+/// - Markdown code fences and text around the JSON value are removed.
+/// - Trailing commas in objects and arrays are removed.
+/// - Truncated output is completed: open strings, literals, arrays and objects are closed, and a
+///   property whose value is missing is removed.
+///
+/// Valid JSON is returned unchanged, apart from surrounding whitespace.
+pub fn repair_json(text: &str) -> String {
+    let text = strip_code_fence(text.trim());
+    // Skip any text before the value
+    let text = match text.find(['{', '[']) {
+        Some(start) => &text[start..],
+        None => text,
+    };
+
+    let mut repaired = String::with_capacity(text.len() + 8);
+    // The open arrays and objects
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    // The start of an object key whose value hasn't started yet
+    let mut pending_key = None;
+
+    for c in text.chars() {
+        if in_string {
+            repaired.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                let key_position = stack.last() == Some(&'}')
+                    && matches!(last_significant(&repaired), Some('{' | ','));
+                if key_position {
+                    pending_key = Some(repaired.len());
+                } else {
+                    pending_key = None;
+                }
+                in_string = true;
+                repaired.push(c);
+            }
+            '{' | '[' => {
+                pending_key = None;
+                stack.push(if c == '{' { '}' } else { ']' });
+                repaired.push(c);
+            }
+            '}' | ']' => {
+                remove_trailing_comma(&mut repaired);
+                if stack.pop().is_none() {
+                    break;
+                }
+                pending_key = None;
+                repaired.push(c);
+                if stack.is_empty() {
+                    // Skip any text after the value
+                    break;
+                }
+            }
+            ':' => repaired.push(c),
+            c if c.is_whitespace() || c == ',' => repaired.push(c),
+            c => {
+                if last_significant(&repaired) == Some(':') {
+                    pending_key = None;
+                }
+                repaired.push(c);
+            }
+        }
+    }
+
+    if stack.is_empty() && !in_string {
+        return repaired;
+    }
+
+    // The output was truncated
+    if in_string {
+        if escaped {
+            repaired.pop();
+        }
+        repaired.push('"');
+    }
+    if let Some(start) = pending_key {
+        repaired.truncate(start);
+    }
+    complete_value(&mut repaired);
+    while let Some(close) = stack.pop() {
+        remove_trailing_comma(&mut repaired);
+        repaired.push(close);
+    }
+
+    repaired
+}
+
+fn strip_code_fence(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("```") else {
+        return text;
+    };
+    // Skip the language of the fence
+    let rest = rest.split_once('\n').map_or("", |(_, rest)| rest);
+    rest.trim_end().strip_suffix("```").unwrap_or(rest).trim()
+}
+
+fn last_significant(text: &str) -> Option<char> {
+    text.trim_end().chars().next_back()
+}
+
+fn remove_trailing_comma(text: &mut String) {
+    let trimmed = text.trim_end().len();
+    if text[..trimmed].ends_with(',') {
+        text.truncate(trimmed - 1);
+    }
+}
+
+/// Completes a truncated literal or number at the end of `text`, or a missing property value.
+fn complete_value(text: &mut String) {
+    remove_trailing_comma(text);
+    text.truncate(text.trim_end().len());
+
+    let token_start = text
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.')))
+        .map_or(0, |index| index + 1);
+    let token = &text[token_start..];
+    if !token.is_empty() {
+        if let Some(literal) = ["true", "false", "null"]
+            .into_iter()
+            .find(|literal| literal.starts_with(token))
+        {
+            let rest = &literal[token.len()..];
+            text.push_str(rest);
+        } else {
+            let number = token.trim_end_matches(['-', '+', '.', 'e', 'E']);
+            text.truncate(token_start + number.len());
+        }
+    }
+
+    if text.trim_end().ends_with(':') {
+        text.push_str("null");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_json_is_unchanged() {
+        let json = r#"{"name": "Canada", "languages": ["English", "French"], "a,b": "}"}"#;
+        assert_eq!(repair_json(json), json);
+    }
+
+    #[test]
+    fn fences_surrounding_text_and_trailing_commas_are_removed() {
+        let text =
+            "Here you go:\n```json\n{\"languages\": [\"English\", \"French\",],}\n```\nEnjoy!";
+        assert_eq!(repair_json(text), r#"{"languages": ["English", "French"]}"#);
+        assert_eq!(repair_json("Sure! [1, 2] is the answer"), "[1, 2]");
+    }
+
+    #[test]
+    fn truncated_output_is_completed() {
+        let cases = [
+            (r#"{"name": "Can"#, r#"{"name": "Can"}"#),
+            (r#"{"name": "Canada", "langu"#, r#"{"name": "Canada"}"#),
+            (r#"{"name": "Canada", "capital":"#, r#"{"name": "Canada"}"#),
+            (
+                r#"{"languages": ["English", "Fr"#,
+                r#"{"languages": ["English", "Fr"]}"#,
+            ),
+            (r#"{"big": tr"#, r#"{"big": true}"#),
+            (r#"{"population": 38.,"#, r#"{"population": 38}"#),
+            (r#"{"population": -"#, r#"{"population": null}"#),
+            (r#"{"name": "a\"#, r#"{"name": "a"}"#),
+            (r#"[{"a": 1}, {"#, r#"[{"a": 1}, {}]"#),
+        ];
+
+        for (truncated, expected) in cases {
+            assert_eq!(repair_json(truncated), expected, "repairing {truncated}");
+            serde_json::from_str::<serde_json::Value>(&repair_json(truncated)).unwrap();
+        }
+    }
+}
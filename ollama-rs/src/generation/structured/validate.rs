@@ -0,0 +1,449 @@
+//! Validation of model output against the JSON schema of a [`JsonStructure`].
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::generation::parameters::JsonStructure;
+
+/// A part of a value that doesn't satisfy the schema it was validated against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    /// A JSON pointer to the invalid part of the value, such as `/languages/0`, or an empty
+    /// string for the value itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl JsonStructure {
+    /// Validates `value` against the schema, and returns every violation found.
+    ///
+    /// This supports the keywords of the schemas generated by [`JsonStructure::new`]: `type`,
+    /// `enum`, `const`, numeric ranges, string lengths, array items and lengths, object
+    /// properties, `required`, `additionalProperties`, `allOf`, `anyOf`, `oneOf` and `not`.
+    /// Other keywords, such as `pattern` and `format`, are ignored.
+    pub fn validate(&self, value: &Value) -> Result<(), Vec<SchemaViolation>> {
+        let mut violations = Vec::new();
+        validate(self.schema().as_value(), value, "", &mut violations);
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+fn validate(schema: &Value, value: &Value, path: &str, violations: &mut Vec<SchemaViolation>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => return violation(violations, path, "no value is allowed here"),
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(types) = schema.get("type") {
+        let types = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            let message = format!(
+                "expected {}, found {}",
+                types.join(" or "),
+                type_name(value)
+            );
+            // The other keywords don't apply to a value of another type
+            return violation(violations, path, message);
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            let allowed = allowed.iter().map(Value::to_string).collect::<Vec<_>>();
+            let message = format!("expected one of {}, found {value}", allowed.join(", "));
+            violation(violations, path, message);
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            violation(
+                violations,
+                path,
+                format!("expected {expected}, found {value}"),
+            );
+        }
+    }
+
+    match value {
+        Value::Number(number) => {
+            if let Some(number) = number.as_f64() {
+                validate_number(schema, number, path, violations);
+            }
+        }
+        Value::String(string) => validate_string(schema, string, path, violations),
+        Value::Array(items) => validate_array(schema, items, path, violations),
+        Value::Object(object) => validate_object(schema, object, path, violations),
+        _ => {}
+    }
+
+    if let Some(Value::Array(schemas)) = schema.get("allOf") {
+        for schema in schemas {
+            validate(schema, value, path, violations);
+        }
+    }
+    if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+        if !schemas.iter().any(|schema| is_valid(schema, value)) {
+            // Report the violations of the closest alternative of the right type, as for
+            // `Option<T>`, whose schema is `T` or null
+            let closest = schemas
+                .iter()
+                .filter(|schema| accepts_type(schema, value))
+                .map(|schema| {
+                    let mut alternative = Vec::new();
+                    validate(schema, value, path, &mut alternative);
+                    alternative
+                })
+                .min_by_key(Vec::len);
+            match closest {
+                Some(alternative) => violations.extend(alternative),
+                None => violation(violations, path, "doesn't match any of the allowed schemas"),
+            }
+        }
+    }
+    if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+        let matches = schemas
+            .iter()
+            .filter(|schema| is_valid(schema, value))
+            .count();
+        if matches != 1 {
+            let message = format!("expected to match exactly one schema, matches {matches}");
+            violation(violations, path, message);
+        }
+    }
+    if let Some(schema) = schema.get("not") {
+        if is_valid(schema, value) {
+            violation(violations, path, "matches a schema it must not match");
+        }
+    }
+}
+
+fn validate_number(
+    schema: &Map<String, Value>,
+    number: f64,
+    path: &str,
+    violations: &mut Vec<SchemaViolation>,
+) {
+    let bound = |keyword| schema.get(keyword).and_then(Value::as_f64);
+
+    if let Some(minimum) = bound("minimum").filter(|minimum| number < *minimum) {
+        violation(violations, path, format!("must be at least {minimum}"));
+    }
+    if let Some(maximum) = bound("maximum").filter(|maximum| number > *maximum) {
+        violation(violations, path, format!("must be at most {maximum}"));
+    }
+    if let Some(minimum) = bound("exclusiveMinimum").filter(|minimum| number <= *minimum) {
+        violation(violations, path, format!("must be greater than {minimum}"));
+    }
+    if let Some(maximum) = bound("exclusiveMaximum").filter(|maximum| number >= *maximum) {
+        violation(violations, path, format!("must be less than {maximum}"));
+    }
+    if let Some(factor) = bound("multipleOf").filter(|factor| *factor > 0.0) {
+        let quotient = number / factor;
+        if (quotient - quotient.round()).abs() > 1e-9 {
+            violation(violations, path, format!("must be a multiple of {factor}"));
+        }
+    }
+}
+
+fn validate_string(
+    schema: &Map<String, Value>,
+    string: &str,
+    path: &str,
+    violations: &mut Vec<SchemaViolation>,
+) {
+    let length = string.chars().count() as u64;
+
+    if let Some(minimum) = schema.get("minLength").and_then(Value::as_u64) {
+        if length < minimum {
+            violation(
+                violations,
+                path,
+                format!("must be at least {minimum} characters long"),
+            );
+        }
+    }
+    if let Some(maximum) = schema.get("maxLength").and_then(Value::as_u64) {
+        if length > maximum {
+            violation(
+                violations,
+                path,
+                format!("must be at most {maximum} characters long"),
+            );
+        }
+    }
+}
+
+fn validate_array(
+    schema: &Map<String, Value>,
+    items: &[Value],
+    path: &str,
+    violations: &mut Vec<SchemaViolation>,
+) {
+    let length = items.len() as u64;
+
+    if let Some(minimum) = schema.get("minItems").and_then(Value::as_u64) {
+        if length < minimum {
+            violation(
+                violations,
+                path,
+                format!("must have at least {minimum} items"),
+            );
+        }
+    }
+    if let Some(maximum) = schema.get("maxItems").and_then(Value::as_u64) {
+        if length > maximum {
+            violation(
+                violations,
+                path,
+                format!("must have at most {maximum} items"),
+            );
+        }
+    }
+    if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+        let duplicated = items
+            .iter()
+            .enumerate()
+            .any(|(index, item)| items[..index].contains(item));
+        if duplicated {
+            violation(violations, path, "must not have duplicate items");
+        }
+    }
+
+    match schema.get("items") {
+        // A tuple, with one schema per position
+        Some(Value::Array(schemas)) => {
+            for (index, (item, schema)) in items.iter().zip(schemas).enumerate() {
+                validate(schema, item, &format!("{path}/{index}"), violations);
+            }
+            if let Some(additional) = schema.get("additionalItems") {
+                for (index, item) in items.iter().enumerate().skip(schemas.len()) {
+                    validate(additional, item, &format!("{path}/{index}"), violations);
+                }
+            }
+        }
+        Some(schema) => {
+            for (index, item) in items.iter().enumerate() {
+                validate(schema, item, &format!("{path}/{index}"), violations);
+            }
+        }
+        None => {}
+    }
+}
+
+fn validate_object(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    violations: &mut Vec<SchemaViolation>,
+) {
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                violation(
+                    violations,
+                    path,
+                    format!("missing required property `{name}`"),
+                );
+            }
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let additional = schema.get("additionalProperties");
+    for (name, value) in object {
+        let property_path = format!("{path}/{}", escape_pointer(name));
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(schema) => validate(schema, value, &property_path, violations),
+            None => match additional {
+                Some(Value::Bool(false)) => {
+                    violation(violations, &property_path, "unexpected property")
+                }
+                Some(schema) => validate(schema, value, &property_path, violations),
+                None => {}
+            },
+        }
+    }
+
+    let length = object.len() as u64;
+    if let Some(minimum) = schema.get("minProperties").and_then(Value::as_u64) {
+        if length < minimum {
+            violation(
+                violations,
+                path,
+                format!("must have at least {minimum} properties"),
+            );
+        }
+    }
+    if let Some(maximum) = schema.get("maxProperties").and_then(Value::as_u64) {
+        if length > maximum {
+            violation(
+                violations,
+                path,
+                format!("must have at most {maximum} properties"),
+            );
+        }
+    }
+}
+
+fn is_valid(schema: &Value, value: &Value) -> bool {
+    let mut violations = Vec::new();
+    validate(schema, value, "", &mut violations);
+    violations.is_empty()
+}
+
+/// Whether the `type` of a schema, if any, accepts the value.
+fn accepts_type(schema: &Value, value: &Value) -> bool {
+    match schema.get("type") {
+        Some(Value::String(name)) => has_type(value, name),
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| has_type(value, name)),
+        _ => true,
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => match value {
+            Value::Number(number) => {
+                number.is_i64()
+                    || number.is_u64()
+                    || number.as_f64().is_some_and(|number| number.fract() == 0.0)
+            }
+            _ => false,
+        },
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn escape_pointer(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+fn violation(violations: &mut Vec<SchemaViolation>, path: &str, message: impl Into<String>) {
+    violations.push(SchemaViolation {
+        path: path.to_string(),
+        message: message.into(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::JsonSchema;
+    use serde_json::json;
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    enum Temperature {
+        Warm,
+        Cold,
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct City {
+        name: String,
+        #[schemars(range(min = 0))]
+        population: u32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct Country {
+        name: String,
+        capital: Option<City>,
+        #[schemars(length(min = 1))]
+        languages: Vec<String>,
+        temperature: Temperature,
+    }
+
+    #[test]
+    fn valid_values_pass() {
+        let structure = JsonStructure::new::<Country>();
+        let value = json!({
+            "name": "Canada",
+            "capital": { "name": "Ottawa", "population": 1017449 },
+            "languages": ["English", "French"],
+            "temperature": "Cold",
+        });
+
+        assert_eq!(structure.validate(&value), Ok(()));
+        assert_eq!(
+            structure.validate(&json!({
+                "name": "Canada",
+                "capital": null,
+                "languages": ["English"],
+                "temperature": "Warm",
+            })),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn violations_are_reported_with_paths() {
+        let structure = JsonStructure::new::<Country>();
+        let value = json!({
+            "capital": { "name": "Ottawa", "population": -3 },
+            "languages": ["English", 2],
+            "temperature": "Hot",
+        });
+
+        let violations = structure.validate(&value).unwrap_err();
+        let violations = violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            violations,
+            [
+                "missing required property `name`",
+                "/capital/population: must be at least 0",
+                "/languages/1: expected string, found number",
+                "/temperature: expected one of \"Warm\", \"Cold\", found \"Hot\"",
+            ]
+        );
+    }
+}
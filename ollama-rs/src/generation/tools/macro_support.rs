@@ -0,0 +1,105 @@
+//! Support code for the tools generated by the `function` and `tools` macros.
+//!
+//! The conversions below rely on method resolution picking the by-value impl when it applies,
+//! and falling back to the by-reference impl otherwise, so that the generated code doesn't need
+//! to know the concrete output and error types.
+
+use std::fmt::Display;
+
+use serde::Serialize;
+
+/// The output of a tool function, converted with `into_tool_output`.
+pub struct Output<T>(pub T);
+
+/// Outputs convertible into a `String` are passed to the model as is.
+pub trait StringOutput {
+    fn into_tool_output(self) -> super::Result<String>;
+}
+
+impl<T: Into<String>> StringOutput for Output<T> {
+    fn into_tool_output(self) -> super::Result<String> {
+        Ok(self.0.into())
+    }
+}
+
+/// Other outputs are serialized to JSON.
+pub trait SerializeOutput {
+    fn into_tool_output(self) -> super::Result<String>;
+}
+
+impl<T: Serialize> SerializeOutput for &Output<T> {
+    fn into_tool_output(self) -> super::Result<String> {
+        Ok(serde_json::to_string(&self.0)?)
+    }
+}
+
+/// The error of a tool function, converted with `into_tool_error`.
+pub struct ToolError<E>(pub E);
+
+/// Errors convertible into a boxed error keep their type and source.
+pub trait BoxedToolError {
+    fn into_tool_error(self) -> Box<dyn std::error::Error + Send + Sync>;
+}
+
+impl<E: Into<Box<dyn std::error::Error + Send + Sync>>> BoxedToolError for ToolError<E> {
+    fn into_tool_error(self) -> Box<dyn std::error::Error + Send + Sync> {
+        self.0.into()
+    }
+}
+
+/// Other errors are converted from their `Display` representation.
+pub trait DisplayToolError {
+    fn into_tool_error(self) -> Box<dyn std::error::Error + Send + Sync>;
+}
+
+impl<E: Display> DisplayToolError for &ToolError<E> {
+    fn into_tool_error(self) -> Box<dyn std::error::Error + Send + Sync> {
+        self.0.to_string().into()
+    }
+}
+
+/// The output of a handler that returns a `Result`, see [`ResultOutputKind`].
+pub struct ResultKind;
+
+impl ResultKind {
+    pub fn into_result<T, E>(self, output: Result<T, E>) -> Result<T, E> {
+        output
+    }
+}
+
+/// The output of a handler that doesn't return a `Result`, see [`PlainOutputKind`].
+pub struct PlainKind;
+
+impl PlainKind {
+    pub fn into_result<T>(self, output: T) -> Result<T, std::convert::Infallible> {
+        Ok(output)
+    }
+}
+
+/// The return type of the handler of a derived tool isn't visible to the macro, so whether it
+/// is a `Result` is found from the type of its output with `(&output).output_kind()`.
+pub trait ResultOutputKind {
+    fn output_kind(&self) -> ResultKind {
+        ResultKind
+    }
+}
+
+impl<T, E> ResultOutputKind for Result<T, E> {}
+
+/// Outputs that aren't a `Result` are always successful.
+pub trait PlainOutputKind {
+    fn output_kind(&self) -> PlainKind {
+        PlainKind
+    }
+}
+
+impl<T> PlainOutputKind for &T {}
+
+/// Runs a non-async tool function on a blocking thread.
+pub async fn spawn_blocking<F, R>(f: F) -> super::Result<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    Ok(tokio::task::spawn_blocking(f).await?)
+}
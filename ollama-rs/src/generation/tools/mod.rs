@@ -2,17 +2,45 @@
 #[cfg(feature = "tool-implementations")]
 pub mod implementations;
 
-use std::{future::Future, pin::Pin};
+#[doc(hidden)]
+#[cfg(feature = "macros")]
+pub mod macro_support;
+
+#[cfg(feature = "macros")]
+pub use ollama_rs_macros::Tool;
+
+use std::{borrow::Cow, future::Future, pin::Pin, sync::Arc};
 
 use schemars::{generate::SchemaSettings, JsonSchema, Schema};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::{coordinator::Coordinator, history::AsyncChatHistory};
+
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
 /// It's highly recommended that the `JsonSchema` has descriptions for all attributes.
 /// Descriptions can be defined with `#[schemars(description = "Hi I am an attribute")]` above each attribute
-// TODO enforce at compile-time
+///
+/// With the `macros` feature, `#[derive(Tool)]` on a parameters struct generates a unit struct
+/// with the `Tool` suffix implementing this trait, and checks at compile-time that every field
+/// has a description:
+///
+/// ```ignore
+/// /// Get the weather for a given city
+/// #[derive(Deserialize, JsonSchema, Tool)]
+/// #[tool(handler = get_weather)]
+/// struct GetWeather {
+///     /// The city to get the weather for
+///     city: String,
+/// }
+///
+/// async fn get_weather(params: GetWeather) -> Result<String, reqwest::Error> {
+///     reqwest::get(format!("https://wttr.in/{}?format=%C+%t", params.city)).await?.text().await
+/// }
+///
+/// let coordinator = coordinator.add_tool(GetWeatherTool);
+/// ```
 pub trait Tool: Send + Sync {
     type Params: Parameters;
 
@@ -23,6 +51,175 @@ pub trait Tool: Send + Sync {
     /// Note that returning an Err will cause it to be bubbled up. If you want the LLM to handle the error,
     /// return that error as a string.
     fn call(&mut self, parameters: Self::Params) -> impl Future<Output = Result<String>> + Send;
+
+    /// The name of this tool instance, as advertised to the model and used to route tool calls.
+    ///
+    /// Defaults to [`Tool::name`]. Override it when the same tool type can be registered several
+    /// times with different configuration.
+    fn tool_name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(Self::name())
+    }
+
+    /// The description of this tool instance. Defaults to [`Tool::description`].
+    fn tool_description(&self) -> Cow<'_, str> {
+        Cow::Borrowed(Self::description())
+    }
+
+    /// The JSON schema of the parameters of this tool instance.
+    ///
+    /// Defaults to the schema generated from [`Tool::Params`]. Overriding it lets an instance
+    /// narrow what the model is told (e.g. an enum of allowed domains); the arguments are still
+    /// deserialized into [`Tool::Params`].
+    fn parameters_schema(&self) -> Schema {
+        schema_for_params::<Self::Params>()
+    }
+
+    /// Wraps this tool so that it is advertised under `name` instead of [`Tool::tool_name`].
+    fn with_name(self, name: impl Into<String>) -> ConfiguredTool<Self>
+    where
+        Self: Sized,
+    {
+        ConfiguredTool::new(self).with_name(name)
+    }
+
+    /// Wraps this tool so that it is advertised with `description` instead of
+    /// [`Tool::tool_description`].
+    fn with_description(self, description: impl Into<String>) -> ConfiguredTool<Self>
+    where
+        Self: Sized,
+    {
+        ConfiguredTool::new(self).with_description(description)
+    }
+
+    /// Wraps this tool so that it is advertised with `parameters` instead of
+    /// [`Tool::parameters_schema`].
+    fn with_parameters_schema(self, parameters: Schema) -> ConfiguredTool<Self>
+    where
+        Self: Sized,
+    {
+        ConfiguredTool::new(self).with_parameters_schema(parameters)
+    }
+}
+
+/// A [`Tool`] with instance-level overrides for its name, description and parameter schema.
+///
+/// Created with [`Tool::with_name`], [`Tool::with_description`] or
+/// [`Tool::with_parameters_schema`]. This allows registering the same tool type more than once,
+/// e.g. two searchers configured for different regions:
+///
+/// ```ignore
+/// let coordinator = Coordinator::new(ollama, model, history)
+///     .add_tool(DDGSearcher::new().with_name("search_en"))
+///     .add_tool(german_searcher.with_name("search_de").with_description("Search German sites"));
+/// ```
+pub struct ConfiguredTool<T> {
+    inner: T,
+    name: Option<String>,
+    description: Option<String>,
+    parameters: Option<Schema>,
+}
+
+impl<T: Tool> ConfiguredTool<T> {
+    /// Wraps `tool` without any overrides.
+    pub fn new(tool: T) -> Self {
+        Self {
+            inner: tool,
+            name: None,
+            description: None,
+            parameters: None,
+        }
+    }
+
+    /// Overrides the name of the tool.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Overrides the description of the tool.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Overrides the JSON schema of the tool parameters.
+    pub fn with_parameters_schema(mut self, parameters: Schema) -> Self {
+        self.parameters = Some(parameters);
+        self
+    }
+
+    /// Returns a reference to the wrapped tool.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped tool.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the wrapped tool.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Tool> Tool for ConfiguredTool<T> {
+    type Params = T::Params;
+
+    fn name() -> &'static str {
+        T::name()
+    }
+
+    fn description() -> &'static str {
+        T::description()
+    }
+
+    fn call(&mut self, parameters: Self::Params) -> impl Future<Output = Result<String>> + Send {
+        self.inner.call(parameters)
+    }
+
+    fn tool_name(&self) -> Cow<'_, str> {
+        match &self.name {
+            Some(name) => Cow::Borrowed(name),
+            None => self.inner.tool_name(),
+        }
+    }
+
+    fn tool_description(&self) -> Cow<'_, str> {
+        match &self.description {
+            Some(description) => Cow::Borrowed(description),
+            None => self.inner.tool_description(),
+        }
+    }
+
+    fn parameters_schema(&self) -> Schema {
+        match &self.parameters {
+            Some(parameters) => parameters.clone(),
+            None => self.inner.parameters_schema(),
+        }
+    }
+}
+
+fn schema_for_params<P: JsonSchema>() -> Schema {
+    // Ollama doesn't support $refs, so subschemas are inlined
+    let mut settings = SchemaSettings::draft07();
+    settings.inline_subschemas = true;
+    let generator = settings.into_generator();
+
+    generator.into_root_schema_for::<P>()
+}
+
+/// A group of tools bound to a shared instance, e.g. one holding a DB pool or an HTTP client.
+///
+/// This is usually implemented with the `#[tools]` attribute on an `impl` block (requires the
+/// `macros` feature), which turns every documented `async fn(&self, ...)` into a tool.
+pub trait ToolGroup: Send + Sync + 'static {
+    /// Registers every tool of the group, bound to `self`, on `coordinator`.
+    fn add_tools<C: AsyncChatHistory>(
+        self: Arc<Self>,
+        coordinator: Coordinator<C>,
+    ) -> Coordinator<C>;
 }
 
 pub trait Parameters: DeserializeOwned + JsonSchema {}
@@ -70,19 +267,29 @@ pub struct ToolInfo {
 impl ToolInfo {
     /// Builds the JSON schema information Ollama needs to make a [`Tool`]
     /// available to the model.
+    ///
+    /// This uses the type-level [`Tool::name`] and [`Tool::description`]. Use
+    /// [`ToolInfo::from_tool`] to take instance-level overrides into account.
     pub fn new<P: Parameters, T: Tool<Params = P>>() -> Self {
-        let mut settings = SchemaSettings::draft07();
-        settings.inline_subschemas = true;
-        let generator = settings.into_generator();
-
-        let parameters = generator.into_root_schema_for::<T::Params>();
-
         Self {
             tool_type: ToolType::Function,
             function: ToolFunctionInfo {
                 name: T::name().to_string(),
                 description: T::description().to_string(),
-                parameters,
+                parameters: schema_for_params::<T::Params>(),
+            },
+        }
+    }
+
+    /// Builds the JSON schema information for a specific tool instance, using
+    /// [`Tool::tool_name`], [`Tool::tool_description`] and [`Tool::parameters_schema`].
+    pub fn from_tool<T: Tool>(tool: &T) -> Self {
+        Self {
+            tool_type: ToolType::Function,
+            function: ToolFunctionInfo {
+                name: tool.tool_name().into_owned(),
+                description: tool.tool_description().into_owned(),
+                parameters: tool.parameters_schema(),
             },
         }
     }
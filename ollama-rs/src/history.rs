@@ -1,6 +1,41 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    future::{ready, Future},
+};
+#[cfg(feature = "tokio")]
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use crate::generation::chat::ChatMessage;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::HistoryError, generation::chat::ChatMessage};
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub mod jsonl;
+#[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub mod summary;
+pub mod transcript;
+pub mod tree;
+pub mod trim;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub use jsonl::{JsonlHistory, JsonlStore};
+#[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
+#[cfg(feature = "sqlite")]
+pub use sqlite::{SqliteHistory, SqliteStore};
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub use summary::SummarizingHistory;
+pub use tree::{ConversationTree, NodeId};
+pub use trim::{TrimmedHistory, Trimming};
 
 /// A trait for managing chat message history.
 ///
@@ -18,6 +53,12 @@ pub trait ChatHistory {
     /// The messages are returned as a `Cow` (Clone on Write) to allow for
     /// efficient borrowing or cloning as needed.
     fn messages(&self) -> Cow<'_, [ChatMessage]>;
+    /// Returns the trimming applied to the messages before each request, if any.
+    ///
+    /// See [`TrimmedHistory`] to trim an existing history.
+    fn trimming(&mut self) -> Option<&mut Trimming> {
+        None
+    }
 }
 
 impl ChatHistory for Vec<ChatMessage> {
@@ -38,3 +79,109 @@ impl ChatHistory for Vec<ChatMessage> {
         Cow::Borrowed(self)
     }
 }
+
+/// An asynchronous chat history, for histories backed by a database or a remote service.
+///
+/// This is the trait taken by [`Ollama::send_chat_messages_with_history`] and by the
+/// [`Coordinator`]. It is implemented for every [`ChatHistory`], so synchronous histories such as
+/// `Vec<ChatMessage>` can be used as is.
+///
+/// The history itself doesn't have to be `Send`, but the futures of its methods do, so that the
+/// requests of `Send` histories can be sent from spawned tasks.
+///
+/// [`Ollama::send_chat_messages_with_history`]: crate::Ollama::send_chat_messages_with_history
+/// [`Coordinator`]: crate::coordinator::Coordinator
+pub trait AsyncChatHistory {
+    /// Adds a chat message to the history.
+    fn push_message(
+        &mut self,
+        message: ChatMessage,
+    ) -> impl Future<Output = Result<(), HistoryError>> + Send;
+    /// Returns the chat messages of the history.
+    fn fetch_messages(&self)
+        -> impl Future<Output = Result<Vec<ChatMessage>, HistoryError>> + Send;
+    /// Returns the trimming applied to the messages before each request, if any.
+    fn trimming(&mut self) -> Option<&mut Trimming> {
+        None
+    }
+}
+
+impl<C: ChatHistory> AsyncChatHistory for C {
+    fn push_message(
+        &mut self,
+        message: ChatMessage,
+    ) -> impl Future<Output = Result<(), HistoryError>> + Send {
+        ChatHistory::push(self, message);
+        ready(Ok(()))
+    }
+
+    fn fetch_messages(
+        &self,
+    ) -> impl Future<Output = Result<Vec<ChatMessage>, HistoryError>> + Send {
+        ready(Ok(ChatHistory::messages(self).into_owned()))
+    }
+
+    fn trimming(&mut self) -> Option<&mut Trimming> {
+        ChatHistory::trimming(self)
+    }
+}
+
+/// A message of a persistent history, with the time it was stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMessage {
+    /// The time the message was stored, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub message: ChatMessage,
+}
+
+/// A conversation of a persistent history store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversationInfo {
+    pub id: String,
+    /// The time the conversation was created, in milliseconds since the Unix epoch.
+    pub created_at: u64,
+    /// The time of the last message, or the creation time if there are no messages.
+    pub updated_at: u64,
+    pub message_count: usize,
+}
+
+/// Milliseconds since the Unix epoch.
+#[cfg(feature = "tokio")]
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// A new conversation ID, ordered by creation time.
+#[cfg(feature = "tokio")]
+pub(crate) fn new_conversation_id() -> String {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed) & 0xffff;
+    format!("{:012x}-{counter:04x}", now_millis())
+}
+
+/// Runs the blocking I/O of a persistent history on a blocking thread.
+#[cfg(feature = "tokio")]
+pub(crate) async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, HistoryError> + Send + 'static,
+) -> Result<T, HistoryError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(std::io::Error::from)?
+}
+
+/// Conversation IDs are used in file names, so they are restricted to a safe set of characters.
+#[cfg(feature = "tokio")]
+pub(crate) fn validate_conversation_id(id: &str) -> Result<(), HistoryError> {
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(HistoryError::InvalidConversationId(id.to_string()));
+    }
+
+    Ok(())
+}
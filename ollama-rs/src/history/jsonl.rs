@@ -0,0 +1,309 @@
+//! A chat history stored in JSON Lines files, one file per conversation.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::HistoryError, generation::chat::ChatMessage};
+
+use super::{
+    new_conversation_id, now_millis, run_blocking, validate_conversation_id, AsyncChatHistory,
+    ConversationInfo, StoredMessage,
+};
+
+/// The first line of a conversation file.
+#[derive(Serialize, Deserialize)]
+struct ConversationHeader {
+    id: String,
+    created_at: u64,
+}
+
+/// A directory of conversations, each stored in a `<id>.jsonl` file.
+///
+/// The first line of a file holds the conversation ID and creation time, and every other line
+/// holds a [`StoredMessage`]. The methods of the store do blocking file I/O, unlike the pushes of
+/// its histories.
+#[derive(Debug, Clone)]
+pub struct JsonlStore {
+    dir: PathBuf,
+}
+
+impl JsonlStore {
+    /// Opens the store in `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, HistoryError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self { dir })
+    }
+
+    /// Creates a conversation with a new ID.
+    pub fn create(&self) -> Result<JsonlHistory, HistoryError> {
+        self.create_with_id(new_conversation_id())
+    }
+
+    /// Creates a conversation with the given ID.
+    pub fn create_with_id(&self, id: impl Into<String>) -> Result<JsonlHistory, HistoryError> {
+        let id = id.into();
+        let path = self.path(&id)?;
+
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                return Err(HistoryError::ConversationExists(id))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let header = ConversationHeader {
+            id: id.clone(),
+            created_at: now_millis(),
+        };
+        writeln!(file, "{}", serde_json::to_string(&header)?)?;
+
+        Ok(JsonlHistory {
+            id,
+            path,
+            created_at: header.created_at,
+            messages: Vec::new(),
+            timestamps: Vec::new(),
+        })
+    }
+
+    /// Loads the conversation with the given ID.
+    pub fn load(&self, id: &str) -> Result<JsonlHistory, HistoryError> {
+        let path = self.path(id)?;
+
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Err(HistoryError::ConversationNotFound(id.to_string()))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut lines = BufReader::new(file).lines();
+        let header = match lines.next() {
+            Some(line) => serde_json::from_str::<ConversationHeader>(&line?)?,
+            None => return Err(HistoryError::ConversationNotFound(id.to_string())),
+        };
+
+        let mut messages = Vec::new();
+        let mut timestamps = Vec::new();
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let stored = serde_json::from_str::<StoredMessage>(&line)?;
+            timestamps.push(stored.timestamp);
+            messages.push(stored.message);
+        }
+
+        Ok(JsonlHistory {
+            id: header.id,
+            path,
+            created_at: header.created_at,
+            messages,
+            timestamps,
+        })
+    }
+
+    /// Lists the conversations of the store, most recently updated first. Files that can't be
+    /// read are skipped with a warning, so that one corrupt file doesn't hide the others.
+    pub fn list(&self) -> Result<Vec<ConversationInfo>, HistoryError> {
+        let mut conversations = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_none_or(|extension| extension != "jsonl")
+            {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if validate_conversation_id(id).is_err() {
+                continue;
+            }
+
+            match self.load(id) {
+                Ok(history) => conversations.push(history.info()),
+                Err(err) => log::warn!("Skipping conversation file {}: {err}", path.display()),
+            }
+        }
+
+        conversations.sort_by_key(|conversation| std::cmp::Reverse(conversation.updated_at));
+        Ok(conversations)
+    }
+
+    /// Deletes the conversation with the given ID.
+    pub fn delete(&self, id: &str) -> Result<(), HistoryError> {
+        match fs::remove_file(self.path(id)?) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                Err(HistoryError::ConversationNotFound(id.to_string()))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf, HistoryError> {
+        validate_conversation_id(id)?;
+        Ok(self.dir.join(format!("{id}.jsonl")))
+    }
+}
+
+/// A conversation of a [`JsonlStore`]. Every pushed message is appended to its file on a
+/// blocking thread, and is only kept once it is written.
+#[derive(Debug)]
+pub struct JsonlHistory {
+    id: String,
+    path: PathBuf,
+    created_at: u64,
+    messages: Vec<ChatMessage>,
+    timestamps: Vec<u64>,
+}
+
+impl JsonlHistory {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the path of the conversation file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn messages(&self) -> &[ChatMessage] {
+        &self.messages
+    }
+
+    /// Returns the time each message was stored, in milliseconds since the Unix epoch.
+    pub fn timestamps(&self) -> &[u64] {
+        &self.timestamps
+    }
+
+    pub fn info(&self) -> ConversationInfo {
+        ConversationInfo {
+            id: self.id.clone(),
+            created_at: self.created_at,
+            updated_at: self.timestamps.last().copied().unwrap_or(self.created_at),
+            message_count: self.messages.len(),
+        }
+    }
+}
+
+impl AsyncChatHistory for JsonlHistory {
+    async fn push_message(&mut self, message: ChatMessage) -> Result<(), HistoryError> {
+        let stored = StoredMessage {
+            timestamp: now_millis(),
+            message,
+        };
+        let line = serde_json::to_string(&stored)?;
+        let path = self.path.clone();
+        run_blocking(move || {
+            let mut file = OpenOptions::new().append(true).open(path)?;
+            writeln!(file, "{line}")?;
+            Ok(())
+        })
+        .await?;
+
+        self.timestamps.push(stored.timestamp);
+        self.messages.push(stored.message);
+        Ok(())
+    }
+
+    async fn fetch_messages(&self) -> Result<Vec<ChatMessage>, HistoryError> {
+        Ok(self.messages.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::{
+        images::Image,
+        tools::{ToolCall, ToolCallFunction},
+    };
+
+    #[tokio::test]
+    async fn conversations_are_stored_and_loaded() {
+        let dir = std::env::temp_dir().join(format!("ollama-rs-jsonl-{}", new_conversation_id()));
+        let store = JsonlStore::new(&dir).unwrap();
+
+        let mut history = store.create_with_id("weather").unwrap();
+        history
+            .push_message(
+                ChatMessage::user("What is on this picture?".to_string())
+                    .add_image(Image::from_base64("aGVsbG8=")),
+            )
+            .await
+            .unwrap();
+        let mut call = ChatMessage::assistant(String::new());
+        call.tool_calls.push(ToolCall {
+            function: ToolCallFunction {
+                name: "get_weather".to_string(),
+                arguments: serde_json::json!({ "city": "Paris" }),
+            },
+        });
+        history.push_message(call).await.unwrap();
+        store.create().unwrap();
+
+        let loaded = store.load("weather").unwrap();
+        assert_eq!(loaded.messages().len(), 2);
+        assert_eq!(loaded.timestamps(), history.timestamps());
+        assert_eq!(
+            loaded.messages()[0].images.as_ref().unwrap()[0].to_base64(),
+            "aGVsbG8="
+        );
+        assert_eq!(
+            loaded.messages()[1].tool_calls[0].function.arguments["city"],
+            "Paris"
+        );
+        assert!(matches!(
+            store.create_with_id("weather"),
+            Err(HistoryError::ConversationExists(_))
+        ));
+        assert!(matches!(
+            store.load("../weather"),
+            Err(HistoryError::InvalidConversationId(_))
+        ));
+
+        let conversations = store.list().unwrap();
+        assert_eq!(conversations.len(), 2);
+        assert!(conversations.contains(&history.info()));
+
+        store.delete("weather").unwrap();
+        assert!(matches!(
+            store.load("weather"),
+            Err(HistoryError::ConversationNotFound(_))
+        ));
+        assert_eq!(store.list().unwrap().len(), 1);
+
+        // A corrupt file is skipped, and can't be loaded
+        fs::write(dir.join("corrupt.jsonl"), "not JSON\n").unwrap();
+        assert_eq!(store.list().unwrap().len(), 1);
+        assert!(matches!(
+            store.load("corrupt"),
+            Err(HistoryError::JsonError(_))
+        ));
+
+        // Writing to the deleted conversation fails, and the message isn't kept
+        let error = history
+            .push_message(ChatMessage::user("Lost".to_string()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, HistoryError::IoError(_)));
+        assert_eq!(history.messages().len(), 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
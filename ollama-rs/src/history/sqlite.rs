@@ -0,0 +1,340 @@
+//! A chat history stored in a SQLite database.
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{
+    error::HistoryError,
+    generation::chat::{ChatMessage, MessageRole},
+};
+
+use super::{new_conversation_id, now_millis, run_blocking, AsyncChatHistory, ConversationInfo};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS conversations (
+    id TEXT PRIMARY KEY,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    conversation_id TEXT NOT NULL REFERENCES conversations (id),
+    created_at INTEGER NOT NULL,
+    role TEXT NOT NULL,
+    content TEXT NOT NULL,
+    thinking TEXT,
+    images TEXT,
+    tool_calls TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS messages_conversation_id ON messages (conversation_id, id);
+";
+
+/// A SQLite database of conversations.
+///
+/// The store can be cloned; clones share the same connection. Its methods run the queries on
+/// the current thread, waiting for the connection if a message is being inserted, so async code
+/// should call them from [`tokio::task::spawn_blocking`]. Only the histories push their messages
+/// on a blocking thread.
+#[derive(Clone)]
+pub struct SqliteStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Opens the database at `path`, creating it and its tables if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, HistoryError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Opens an in-memory database, which is lost when the last clone of the store is dropped.
+    pub fn open_in_memory() -> Result<Self, HistoryError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Uses an existing connection, creating the tables if needed.
+    ///
+    /// Foreign keys are enforced on the connection, so that a history can't add messages to a
+    /// deleted conversation.
+    pub fn from_connection(connection: Connection) -> Result<Self, HistoryError> {
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Creates a conversation with a new ID.
+    pub fn create(&self) -> Result<SqliteHistory, HistoryError> {
+        self.create_with_id(new_conversation_id())
+    }
+
+    /// Creates a conversation with the given ID.
+    pub fn create_with_id(&self, id: impl Into<String>) -> Result<SqliteHistory, HistoryError> {
+        let id = id.into();
+        let created_at = now_millis();
+
+        let inserted = self.connection.lock().unwrap().execute(
+            "INSERT OR IGNORE INTO conversations (id, created_at) VALUES (?1, ?2)",
+            params![id, created_at as i64],
+        )?;
+        if inserted == 0 {
+            return Err(HistoryError::ConversationExists(id));
+        }
+
+        Ok(SqliteHistory {
+            id,
+            connection: Arc::clone(&self.connection),
+            created_at,
+            messages: Vec::new(),
+            timestamps: Vec::new(),
+        })
+    }
+
+    /// Loads the conversation with the given ID.
+    pub fn load(&self, id: &str) -> Result<SqliteHistory, HistoryError> {
+        let connection = self.connection.lock().unwrap();
+
+        let created_at = connection
+            .query_row(
+                "SELECT created_at FROM conversations WHERE id = ?1",
+                [id],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+            .ok_or_else(|| HistoryError::ConversationNotFound(id.to_string()))?
+            as u64;
+
+        let mut statement = connection.prepare(
+            "SELECT created_at, role, content, thinking, images, tool_calls
+             FROM messages WHERE conversation_id = ?1 ORDER BY id",
+        )?;
+        let rows = statement.query_map([id], |row| {
+            Ok((
+                row.get::<_, i64>(0)? as u64,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+
+        let mut messages = Vec::new();
+        let mut timestamps = Vec::new();
+        for row in rows {
+            let (timestamp, role, content, thinking, images, tool_calls) = row?;
+
+            let mut message = ChatMessage::new(
+                serde_json::from_value::<MessageRole>(serde_json::Value::String(role))?,
+                content,
+            );
+            message.thinking = thinking;
+            message.images = images
+                .map(|images| serde_json::from_str(&images))
+                .transpose()?;
+            message.tool_calls = serde_json::from_str(&tool_calls)?;
+
+            timestamps.push(timestamp);
+            messages.push(message);
+        }
+
+        Ok(SqliteHistory {
+            id: id.to_string(),
+            connection: Arc::clone(&self.connection),
+            created_at,
+            messages,
+            timestamps,
+        })
+    }
+
+    /// Lists the conversations of the store, most recently updated first.
+    pub fn list(&self) -> Result<Vec<ConversationInfo>, HistoryError> {
+        let connection = self.connection.lock().unwrap();
+
+        let mut statement = connection.prepare(
+            "SELECT c.id, c.created_at, COALESCE(MAX(m.created_at), c.created_at), COUNT(m.id)
+             FROM conversations c LEFT JOIN messages m ON m.conversation_id = c.id
+             GROUP BY c.id ORDER BY 3 DESC",
+        )?;
+        let conversations = statement
+            .query_map([], |row| {
+                Ok(ConversationInfo {
+                    id: row.get(0)?,
+                    created_at: row.get::<_, i64>(1)? as u64,
+                    updated_at: row.get::<_, i64>(2)? as u64,
+                    message_count: row.get::<_, i64>(3)? as usize,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(conversations)
+    }
+
+    /// Deletes the conversation with the given ID and its messages.
+    pub fn delete(&self, id: &str) -> Result<(), HistoryError> {
+        let mut connection = self.connection.lock().unwrap();
+
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM messages WHERE conversation_id = ?1", [id])?;
+        let deleted = transaction.execute("DELETE FROM conversations WHERE id = ?1", [id])?;
+        transaction.commit()?;
+
+        if deleted == 0 {
+            return Err(HistoryError::ConversationNotFound(id.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// A conversation of a [`SqliteStore`]. Every pushed message is inserted in the database on a
+/// blocking thread, and is only kept once it is inserted.
+pub struct SqliteHistory {
+    id: String,
+    connection: Arc<Mutex<Connection>>,
+    created_at: u64,
+    messages: Vec<ChatMessage>,
+    timestamps: Vec<u64>,
+}
+
+impl SqliteHistory {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn messages(&self) -> &[ChatMessage] {
+        &self.messages
+    }
+
+    /// Returns the time each message was stored, in milliseconds since the Unix epoch.
+    pub fn timestamps(&self) -> &[u64] {
+        &self.timestamps
+    }
+
+    pub fn info(&self) -> ConversationInfo {
+        ConversationInfo {
+            id: self.id.clone(),
+            created_at: self.created_at,
+            updated_at: self.timestamps.last().copied().unwrap_or(self.created_at),
+            message_count: self.messages.len(),
+        }
+    }
+}
+
+impl AsyncChatHistory for SqliteHistory {
+    async fn push_message(&mut self, message: ChatMessage) -> Result<(), HistoryError> {
+        let timestamp = now_millis();
+        let role = serde_json::to_value(&message.role)?;
+        let images = message
+            .images
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let tool_calls = serde_json::to_string(&message.tool_calls)?;
+        let (id, content, thinking) = (
+            self.id.clone(),
+            message.content.clone(),
+            message.thinking.clone(),
+        );
+
+        let connection = Arc::clone(&self.connection);
+        run_blocking(move || {
+            connection.lock().unwrap().execute(
+                "INSERT INTO messages (conversation_id, created_at, role, content, thinking, images, tool_calls)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    id,
+                    timestamp as i64,
+                    role.as_str(),
+                    content,
+                    thinking,
+                    images,
+                    tool_calls,
+                ],
+            )?;
+            Ok(())
+        })
+        .await?;
+
+        self.timestamps.push(timestamp);
+        self.messages.push(message);
+        Ok(())
+    }
+
+    async fn fetch_messages(&self) -> Result<Vec<ChatMessage>, HistoryError> {
+        Ok(self.messages.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::{
+        images::Image,
+        tools::{ToolCall, ToolCallFunction},
+    };
+
+    #[tokio::test]
+    async fn conversations_are_stored_and_loaded() {
+        let store = SqliteStore::open_in_memory().unwrap();
+
+        let mut history = store.create_with_id("weather").unwrap();
+        history
+            .push_message(
+                ChatMessage::user("What is on this picture?".to_string())
+                    .add_image(Image::from_base64("aGVsbG8=")),
+            )
+            .await
+            .unwrap();
+        let mut call = ChatMessage::assistant(String::new());
+        call.tool_calls.push(ToolCall {
+            function: ToolCallFunction {
+                name: "get_weather".to_string(),
+                arguments: serde_json::json!({ "city": "Paris" }),
+            },
+        });
+        history.push_message(call).await.unwrap();
+        store.create().unwrap();
+
+        let loaded = store.load("weather").unwrap();
+        assert_eq!(loaded.messages().len(), 2);
+        assert_eq!(loaded.timestamps(), history.timestamps());
+        assert_eq!(loaded.messages()[0].role, MessageRole::User);
+        assert_eq!(
+            loaded.messages()[0].images.as_ref().unwrap()[0].to_base64(),
+            "aGVsbG8="
+        );
+        assert_eq!(
+            loaded.messages()[1].tool_calls[0].function.arguments["city"],
+            "Paris"
+        );
+        assert!(matches!(
+            store.create_with_id("weather"),
+            Err(HistoryError::ConversationExists(_))
+        ));
+
+        let conversations = store.list().unwrap();
+        assert_eq!(conversations.len(), 2);
+        assert!(conversations.contains(&history.info()));
+
+        store.delete("weather").unwrap();
+        assert!(matches!(
+            store.load("weather"),
+            Err(HistoryError::ConversationNotFound(_))
+        ));
+        assert_eq!(store.list().unwrap().len(), 1);
+
+        // The conversation is gone, so the message can't reference it
+        assert!(history
+            .push_message(ChatMessage::user("Lost".to_string()))
+            .await
+            .is_err());
+        assert_eq!(history.messages().len(), 2);
+        let recreated = store.create_with_id("weather").unwrap();
+        assert!(store.load(recreated.id()).unwrap().messages().is_empty());
+    }
+}
@@ -0,0 +1,389 @@
+//! A chat history that compresses older turns into a summary written by a model.
+
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::Notify;
+
+use crate::{
+    generation::chat::{request::ChatMessageRequest, ChatMessage, MessageRole},
+    Ollama,
+};
+
+use super::{
+    trim::{CharTokenEstimator, TokenEstimator},
+    ChatHistory,
+};
+
+const DEFAULT_PROMPT: &str = "You compress conversations. Summarize the conversation below in a \
+few sentences, keeping names, facts, decisions and open questions. If a previous summary is \
+given, update it with the new messages. Only answer with the summary.";
+
+/// A [`ChatHistory`] that summarizes older messages with a model once they exceed a token
+/// threshold.
+///
+/// The leading system messages and the most recent messages are always sent as is; the messages
+/// in between are replaced by a single system message holding the summary, which is updated
+/// each time the threshold is crossed again.
+///
+/// Summarization runs in the background on the current Tokio runtime, so `push` never waits for
+/// it: the summary is used from the first request after it is done. If summarization fails, the
+/// older messages are dropped instead, as with a sliding window.
+///
+/// ```ignore
+/// let history = SummarizingHistory::new(ollama.clone(), "llama3.2:1b")
+///     .threshold_tokens(4096)
+///     .keep_recent(8);
+/// let coordinator = Coordinator::new(ollama, "qwen2.5:32b".to_string(), history);
+/// ```
+pub struct SummarizingHistory {
+    ollama: Ollama,
+    model: String,
+    prompt: String,
+    threshold_tokens: usize,
+    keep_recent: usize,
+    estimator: Arc<dyn TokenEstimator>,
+    messages: Vec<ChatMessage>,
+    state: Arc<Mutex<SummaryState>>,
+    /// Notified when a summary job is done, successfully or not
+    finished: Arc<Notify>,
+}
+
+#[derive(Default)]
+struct SummaryState {
+    summary: Option<String>,
+    /// The number of messages covered by the summary, or dropped
+    compressed: usize,
+    pending: bool,
+}
+
+impl SummarizingHistory {
+    /// Creates a history summarized by `model`, which can be smaller than the chat model.
+    pub fn new(ollama: Ollama, model: impl Into<String>) -> Self {
+        Self {
+            ollama,
+            model: model.into(),
+            prompt: DEFAULT_PROMPT.to_string(),
+            threshold_tokens: 2048,
+            keep_recent: 6,
+            estimator: Arc::new(CharTokenEstimator::default()),
+            messages: Vec::new(),
+            state: Arc::default(),
+            finished: Arc::default(),
+        }
+    }
+
+    /// Sets the system prompt used to write the summary.
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Sets the number of tokens of uncompressed messages above which older messages are
+    /// summarized. (Default: 2048)
+    pub fn threshold_tokens(mut self, threshold_tokens: usize) -> Self {
+        self.threshold_tokens = threshold_tokens;
+        self
+    }
+
+    /// Sets the number of recent messages that are never summarized. (Default: 6)
+    pub fn keep_recent(mut self, keep_recent: usize) -> Self {
+        self.keep_recent = keep_recent;
+        self
+    }
+
+    /// Sets the token estimator. (Default: [`CharTokenEstimator`])
+    pub fn estimator(mut self, estimator: impl TokenEstimator + 'static) -> Self {
+        self.estimator = Arc::new(estimator);
+        self
+    }
+
+    /// Returns the current summary of the older messages, if any.
+    pub fn summary(&self) -> Option<String> {
+        self.state.lock().unwrap().summary.clone()
+    }
+
+    /// Returns every message pushed to the history, including the summarized ones.
+    pub fn all_messages(&self) -> &[ChatMessage] {
+        &self.messages
+    }
+
+    /// Summarizes the older messages now, whether or not the threshold is crossed, and waits for
+    /// the summary. A summary being written in the background is waited for first.
+    ///
+    /// If summarization fails, the older messages are dropped and the error is returned.
+    pub async fn summarize(&mut self) -> crate::error::Result<()> {
+        loop {
+            // Created before the job is checked, so that its end can't be missed
+            let finished = self.finished.notified();
+            match self.start_job() {
+                JobStart::Started(job) => return job.run().await,
+                JobStart::Pending => finished.await,
+                JobStart::Nothing => return Ok(()),
+            }
+        }
+    }
+
+    fn system_messages(&self) -> usize {
+        self.messages
+            .iter()
+            .take_while(|message| message.role == MessageRole::System)
+            .count()
+    }
+
+    /// Marks the messages to compress as pending, unless a summary is already being written or
+    /// there is nothing to compress.
+    fn start_job(&self) -> JobStart {
+        let mut state = self.state.lock().unwrap();
+        if state.pending {
+            return JobStart::Pending;
+        }
+
+        let start = state.compressed.max(self.system_messages());
+        let mut end = self.messages.len().saturating_sub(self.keep_recent);
+        // Tool results must stay with the call they answer
+        while end < self.messages.len() && self.messages[end].role == MessageRole::Tool {
+            end += 1;
+        }
+        if end <= start {
+            return JobStart::Nothing;
+        }
+
+        state.pending = true;
+        JobStart::Started(Box::new(SummaryJob {
+            ollama: self.ollama.clone(),
+            model: self.model.clone(),
+            prompt: self.prompt.clone(),
+            previous: state.summary.clone(),
+            messages: self.messages[start..end].to_vec(),
+            end,
+            state: Arc::clone(&self.state),
+            finished: Arc::clone(&self.finished),
+        }))
+    }
+
+    fn uncompressed_tokens(&self) -> usize {
+        let start = self
+            .state
+            .lock()
+            .unwrap()
+            .compressed
+            .max(self.system_messages());
+        self.messages[start..]
+            .iter()
+            .map(|message| self.estimator.estimate(message))
+            .sum()
+    }
+}
+
+impl ChatHistory for SummarizingHistory {
+    fn push(&mut self, message: ChatMessage) {
+        self.messages.push(message);
+
+        if self.uncompressed_tokens() <= self.threshold_tokens {
+            return;
+        }
+
+        let JobStart::Started(job) = self.start_job() else {
+            return;
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(job.run());
+            }
+            Err(_) => job.fail("no Tokio runtime to summarize the history on"),
+        }
+    }
+
+    fn messages(&self) -> Cow<'_, [ChatMessage]> {
+        let state = self.state.lock().unwrap();
+        let system = self.system_messages();
+        let start = state.compressed.max(system);
+
+        let mut messages = Vec::with_capacity(system + 1 + self.messages.len() - start);
+        messages.extend_from_slice(&self.messages[..system]);
+        if let Some(summary) = &state.summary {
+            messages.push(ChatMessage::system(format!(
+                "Summary of the earlier conversation:\n{summary}"
+            )));
+        }
+        messages.extend_from_slice(&self.messages[start..]);
+
+        Cow::Owned(messages)
+    }
+}
+
+enum JobStart {
+    Started(Box<SummaryJob>),
+    /// A summary is already being written
+    Pending,
+    /// There is nothing to compress
+    Nothing,
+}
+
+/// The summarization of `messages`, which are then replaced by the summary.
+struct SummaryJob {
+    ollama: Ollama,
+    model: String,
+    prompt: String,
+    previous: Option<String>,
+    messages: Vec<ChatMessage>,
+    end: usize,
+    state: Arc<Mutex<SummaryState>>,
+    finished: Arc<Notify>,
+}
+
+impl SummaryJob {
+    async fn run(self) -> crate::error::Result<()> {
+        let mut transcript = String::new();
+        if let Some(previous) = &self.previous {
+            transcript.push_str(&format!("Previous summary:\n{previous}\n\nNew messages:\n"));
+        }
+        for message in &self.messages {
+            let role = match message.role {
+                MessageRole::User => "user",
+                MessageRole::Assistant => "assistant",
+                MessageRole::System => "system",
+                MessageRole::Tool => "tool",
+            };
+            transcript.push_str(&format!("{role}: {}\n", message.content));
+        }
+
+        let request = ChatMessageRequest::new(
+            self.model.clone(),
+            vec![
+                ChatMessage::system(self.prompt.clone()),
+                ChatMessage::user(transcript),
+            ],
+        );
+
+        match self.ollama.send_chat_messages(request).await {
+            Ok(response) if !response.message.content.trim().is_empty() => {
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.summary = Some(response.message.content.trim().to_string());
+                    state.compressed = self.end;
+                    state.pending = false;
+                }
+                self.finished.notify_waiters();
+                Ok(())
+            }
+            Ok(_) => {
+                self.fail("the model returned an empty summary");
+                Err(crate::error::OllamaError::Other(
+                    "The model returned an empty summary".to_string(),
+                ))
+            }
+            Err(error) => {
+                self.fail(&error.to_string());
+                Err(error)
+            }
+        }
+    }
+
+    /// Falls back to dropping the messages, keeping the previous summary.
+    fn fail(self, reason: &str) {
+        log::warn!("Failed to summarize the chat history, dropping older messages: {reason}");
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.compressed = self.end;
+            state.pending = false;
+        }
+        self.finished.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_util::{serve, Response};
+
+    fn history(ollama: Ollama) -> SummarizingHistory {
+        let mut history = SummarizingHistory::new(ollama, "small")
+            .threshold_tokens(usize::MAX)
+            .keep_recent(2);
+        history.push(ChatMessage::system("Be brief.".to_string()));
+        for i in 0..3 {
+            history.push(ChatMessage::user(format!("question {i}")));
+            history.push(ChatMessage::assistant(format!("answer {i}")));
+        }
+        history
+    }
+
+    fn contents(history: &SummarizingHistory) -> Vec<String> {
+        history
+            .messages()
+            .iter()
+            .map(|message| message.content.clone())
+            .collect()
+    }
+
+    fn summary_response() -> Response {
+        Response::json(json!({
+            "model": "small",
+            "created_at": "2026-01-01T00:00:00Z",
+            "message": { "role": "assistant", "content": "Two questions were answered." },
+            "done": true,
+        }))
+    }
+
+    #[tokio::test]
+    async fn older_messages_are_summarized() {
+        let (ollama, _) = serve(|_| summary_response()).await;
+        let mut history = history(ollama);
+
+        history.summarize().await.expect("summary is written");
+
+        assert_eq!(
+            history.summary().as_deref(),
+            Some("Two questions were answered.")
+        );
+        assert_eq!(
+            contents(&history),
+            [
+                "Be brief.",
+                "Summary of the earlier conversation:\nTwo questions were answered.",
+                "question 2",
+                "answer 2",
+            ]
+        );
+        assert_eq!(history.all_messages().len(), 7);
+    }
+
+    #[tokio::test]
+    async fn summarize_waits_for_the_background_summary() {
+        let (ollama, mut requests) = serve(|_| summary_response()).await;
+        let mut history = history(ollama).threshold_tokens(0);
+
+        // Crossing the threshold starts a summary in the background
+        history.push(ChatMessage::user("question 3".to_string()));
+        assert!(history.state.lock().unwrap().pending);
+
+        history.summarize().await.expect("summary is written");
+        assert_eq!(
+            history.summary().as_deref(),
+            Some("Two questions were answered.")
+        );
+        assert_eq!(contents(&history)[2..], ["answer 2", "question 3"]);
+
+        // Only the background summary was requested
+        requests.recv().await.unwrap();
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_summary_falls_back_to_truncation() {
+        let (ollama, _) = serve(|_| Response::json(json!({ "error": "boom" })).status(500)).await;
+        let mut history = history(ollama);
+
+        history.summarize().await.expect_err("summary fails");
+
+        assert_eq!(history.summary(), None);
+        assert_eq!(contents(&history), ["Be brief.", "question 2", "answer 2"]);
+    }
+}
@@ -0,0 +1,685 @@
+//! Conversions between chat messages and common transcript formats: OpenAI chat messages,
+//! ShareGPT conversations and Markdown, and a JSON Lines export for fine-tuning datasets.
+//!
+//! Images, thinking and tool calls are kept in every format. Converting messages to OpenAI chat
+//! messages and back gives the same messages; ShareGPT and Markdown transcripts mix the contents
+//! with their own markup, so a few contents don't round-trip, as documented by [`from_sharegpt`]
+//! and [`from_markdown`].
+
+use std::{borrow::Cow, fmt::Write as _, io::Write};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    error::HistoryError,
+    generation::{
+        chat::{ChatMessage, MessageRole},
+        images::Image,
+        tools::{ToolCall, ToolCallFunction},
+    },
+};
+
+/// Converts messages to OpenAI chat completion messages.
+///
+/// Images become `image_url` content parts with data URLs, and thinking becomes
+/// `reasoning_content`. Ollama doesn't identify tool calls, so they get sequential IDs, which the
+/// tool messages that follow them refer to, in order.
+pub fn to_openai(messages: &[ChatMessage]) -> Value {
+    let mut next_call_id = 0;
+    let mut pending_call_ids = Vec::new();
+    let mut converted = Vec::with_capacity(messages.len());
+
+    for message in messages {
+        let mut object = Map::new();
+        object.insert("role".into(), json!(role_name(&message.role)));
+
+        let content = match &message.images {
+            Some(images) if !images.is_empty() => {
+                let mut parts = vec![json!({ "type": "text", "text": message.content })];
+                parts.extend(images.iter().map(
+                    |image| json!({ "type": "image_url", "image_url": { "url": data_url(image) } }),
+                ));
+                Value::Array(parts)
+            }
+            _ => json!(message.content),
+        };
+        object.insert("content".into(), content);
+
+        if let Some(thinking) = &message.thinking {
+            object.insert("reasoning_content".into(), json!(thinking));
+        }
+
+        if !message.tool_calls.is_empty() {
+            pending_call_ids.clear();
+            let calls = message
+                .tool_calls
+                .iter()
+                .map(|call| {
+                    let id = format!("call_{next_call_id}");
+                    next_call_id += 1;
+                    pending_call_ids.push(id.clone());
+                    json!({
+                        "id": id,
+                        "type": "function",
+                        "function": {
+                            "name": call.function.name,
+                            "arguments": call.function.arguments.to_string(),
+                        },
+                    })
+                })
+                .collect();
+            object.insert("tool_calls".into(), Value::Array(calls));
+            pending_call_ids.reverse();
+        }
+
+        if message.role == MessageRole::Tool {
+            if let Some(id) = pending_call_ids.pop() {
+                object.insert("tool_call_id".into(), json!(id));
+            }
+        }
+
+        converted.push(Value::Object(object));
+    }
+
+    Value::Array(converted)
+}
+
+/// Converts OpenAI chat completion messages to chat messages.
+///
+/// Accepts either an array of messages or an object with a `messages` array, such as a chat
+/// completion request. Images must be base64 data URLs.
+pub fn from_openai(messages: &Value) -> Result<Vec<ChatMessage>, HistoryError> {
+    let messages = messages
+        .get("messages")
+        .unwrap_or(messages)
+        .as_array()
+        .ok_or_else(|| invalid("expected an array of messages"))?;
+
+    messages
+        .iter()
+        .map(|message| {
+            let role = match message.get("role").and_then(Value::as_str) {
+                Some("system" | "developer") => MessageRole::System,
+                Some("user") => MessageRole::User,
+                Some("assistant") => MessageRole::Assistant,
+                Some("tool") => MessageRole::Tool,
+                Some(role) => return Err(invalid(format!("unknown role `{role}`"))),
+                None => return Err(invalid("message without a role")),
+            };
+
+            let mut content = String::new();
+            let mut images = Vec::new();
+            match message.get("content") {
+                None | Some(Value::Null) => {}
+                Some(Value::String(text)) => content.push_str(text),
+                Some(Value::Array(parts)) => {
+                    for part in parts {
+                        match part.get("type").and_then(Value::as_str) {
+                            Some("text") => {
+                                if !content.is_empty() {
+                                    content.push('\n');
+                                }
+                                content.push_str(string_field(part, "text")?);
+                            }
+                            Some("image_url") => {
+                                let url = part
+                                    .pointer("/image_url/url")
+                                    .and_then(Value::as_str)
+                                    .ok_or_else(|| invalid("image part without a URL"))?;
+                                images.push(parse_data_url(url)?);
+                            }
+                            _ => return Err(invalid("unsupported content part")),
+                        }
+                    }
+                }
+                Some(_) => return Err(invalid("expected a string or an array as content")),
+            }
+
+            let mut converted = ChatMessage::new(role, content);
+            if !images.is_empty() {
+                converted.images = Some(images);
+            }
+            converted.thinking = message
+                .get("reasoning_content")
+                .or_else(|| message.get("reasoning"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+
+            if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+                for call in calls {
+                    let function = call
+                        .get("function")
+                        .ok_or_else(|| invalid("tool call without a function"))?;
+                    converted.tool_calls.push(tool_call(
+                        string_field(function, "name")?,
+                        function.get("arguments"),
+                    ));
+                }
+            }
+
+            Ok(converted)
+        })
+        .collect()
+}
+
+/// Converts messages to a ShareGPT conversation, as used by fine-tuning tools.
+///
+/// The result is an object with a `conversations` array of `from`/`value` turns, and an `images`
+/// array of data URLs, each referenced by an `<image>` placeholder at the start of the turn it
+/// belongs to. Thinking is wrapped in `<think>` tags at the start of `gpt` turns, tool calls are
+/// `function_call` turns, and tool results are `observation` turns.
+pub fn to_sharegpt(messages: &[ChatMessage]) -> Value {
+    let mut turns = Vec::new();
+    let mut all_images = Vec::new();
+
+    for message in messages {
+        let mut value = String::new();
+        for image in message.images.iter().flatten() {
+            value.push_str("<image>");
+            all_images.push(json!(data_url(image)));
+        }
+        if let Some(thinking) = &message.thinking {
+            let _ = write!(value, "<think>\n{thinking}\n</think>\n\n");
+        }
+        value.push_str(&message.content);
+
+        let from = match message.role {
+            MessageRole::System => "system",
+            MessageRole::User => "human",
+            MessageRole::Assistant => "gpt",
+            MessageRole::Tool => "observation",
+        };
+        // An assistant message with only tool calls is a single `function_call` turn
+        if message.role != MessageRole::Assistant
+            || !value.is_empty()
+            || message.tool_calls.is_empty()
+        {
+            turns.push(json!({ "from": from, "value": value }));
+        }
+
+        if !message.tool_calls.is_empty() {
+            let mut calls = message
+                .tool_calls
+                .iter()
+                .map(|call| json!({ "name": call.function.name, "arguments": call.function.arguments }))
+                .collect::<Vec<_>>();
+            let calls = if calls.len() == 1 {
+                calls.remove(0)
+            } else {
+                Value::Array(calls)
+            };
+            turns.push(json!({ "from": "function_call", "value": calls.to_string() }));
+        }
+    }
+
+    let mut conversation = json!({ "conversations": turns });
+    if !all_images.is_empty() {
+        conversation["images"] = Value::Array(all_images);
+    }
+    conversation
+}
+
+/// Converts a ShareGPT conversation to chat messages.
+///
+/// A `function_call` turn right after a `gpt` turn is merged into its message. Images must be
+/// base64 data URLs.
+///
+/// ShareGPT has no escaping, so `<image>` placeholders and a leading `<think>` block are read as
+/// images and thinking even if they were written as part of the content.
+pub fn from_sharegpt(conversation: &Value) -> Result<Vec<ChatMessage>, HistoryError> {
+    let turns = conversation
+        .get("conversations")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("expected a `conversations` array"))?;
+    let mut images = conversation
+        .get("images")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+
+    let mut messages = Vec::<ChatMessage>::new();
+    for turn in turns {
+        let from = string_field(turn, "from")?;
+        let value = string_field(turn, "value")?;
+
+        if from == "function_call" {
+            let calls = serde_json::from_str::<Value>(value)?;
+            let calls = match calls {
+                Value::Array(calls) => calls,
+                call => vec![call],
+            };
+            let calls = calls
+                .iter()
+                .map(|call| {
+                    Ok(tool_call(
+                        string_field(call, "name")?,
+                        call.get("arguments"),
+                    ))
+                })
+                .collect::<Result<Vec<_>, HistoryError>>()?;
+
+            match messages.last_mut() {
+                Some(last) if last.role == MessageRole::Assistant && last.tool_calls.is_empty() => {
+                    last.tool_calls = calls;
+                }
+                _ => {
+                    let mut message = ChatMessage::assistant(String::new());
+                    message.tool_calls = calls;
+                    messages.push(message);
+                }
+            }
+            continue;
+        }
+
+        let role = match from {
+            "system" => MessageRole::System,
+            "human" | "user" => MessageRole::User,
+            "gpt" | "assistant" => MessageRole::Assistant,
+            "observation" | "tool" => MessageRole::Tool,
+            from => return Err(invalid(format!("unknown turn `{from}`"))),
+        };
+
+        let image_count = value.matches("<image>").count();
+        let mut message = ChatMessage::new(role, value.replace("<image>", ""));
+        if image_count > 0 {
+            let turn_images = images
+                .by_ref()
+                .take(image_count)
+                .map(|url| {
+                    url.as_str()
+                        .ok_or_else(|| invalid("expected image URLs"))
+                        .and_then(parse_data_url)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if turn_images.len() != image_count {
+                return Err(invalid("more `<image>` placeholders than images"));
+            }
+            message.images = Some(turn_images);
+        }
+
+        if let Some((thinking, content)) = message
+            .content
+            .strip_prefix("<think>")
+            .and_then(|rest| rest.split_once("</think>"))
+        {
+            // Only the line breaks added by `to_sharegpt` are removed
+            let thinking = thinking.strip_prefix('\n').unwrap_or(thinking);
+            let thinking = thinking.strip_suffix('\n').unwrap_or(thinking);
+            let content = content.strip_prefix("\n\n").unwrap_or(content);
+            (message.thinking, message.content) = (Some(thinking.to_string()), content.to_string());
+        }
+
+        messages.push(message);
+    }
+
+    Ok(messages)
+}
+
+/// Converts messages to a Markdown transcript, with a `## <Role>` heading per message.
+///
+/// Thinking is in a collapsed `<details>` block before the content, images are data URL images
+/// and tool calls are JSON code blocks after it. Lines of the thinking and content that would be
+/// read back as such markup, or as a role heading, are escaped with a backslash.
+pub fn to_markdown(messages: &[ChatMessage]) -> String {
+    let mut markdown = String::new();
+
+    for message in messages {
+        let _ = write!(markdown, "## {}\n\n", role_heading(&message.role));
+        if let Some(thinking) = &message.thinking {
+            let _ = write!(
+                markdown,
+                "<details>\n<summary>Thinking</summary>\n\n{}\n\n</details>\n\n",
+                escape_markdown(thinking)
+            );
+        }
+        if !message.content.is_empty() {
+            let _ = write!(markdown, "{}\n\n", escape_markdown(&message.content));
+        }
+        for image in message.images.iter().flatten() {
+            let _ = write!(markdown, "![image]({})\n\n", data_url(image));
+        }
+        for call in &message.tool_calls {
+            let arguments = serde_json::to_string_pretty(&call.function.arguments)
+                .unwrap_or_else(|_| call.function.arguments.to_string());
+            let _ = write!(
+                markdown,
+                "**Tool call:** `{}`\n\n```json\n{arguments}\n```\n\n",
+                call.function.name
+            );
+        }
+    }
+
+    markdown.truncate(markdown.trim_end().len());
+    markdown.push('\n');
+    markdown
+}
+
+/// Parses a Markdown transcript written by [`to_markdown`].
+///
+/// Leading and trailing line breaks of the thinking and content aren't kept, and line endings are
+/// read as `\n`.
+pub fn from_markdown(markdown: &str) -> Result<Vec<ChatMessage>, HistoryError> {
+    let mut blocks = Vec::<(MessageRole, Vec<&str>)>::new();
+
+    for line in markdown.lines() {
+        match (heading_role(line), blocks.last_mut()) {
+            (Some(role), _) => blocks.push((role, Vec::new())),
+            (None, Some((_, lines))) => lines.push(line),
+            (None, None) if line.trim().is_empty() => {}
+            (None, None) => return Err(invalid("expected a role heading first")),
+        }
+    }
+
+    blocks
+        .into_iter()
+        .map(|(role, lines)| parse_markdown_message(role, &lines))
+        .collect()
+}
+
+fn parse_markdown_message(role: MessageRole, lines: &[&str]) -> Result<ChatMessage, HistoryError> {
+    let mut lines = lines
+        .iter()
+        .copied()
+        .skip_while(|line| line.is_empty())
+        .peekable();
+    let mut message = ChatMessage::new(role, String::new());
+
+    if lines.next_if_eq(&"<details>").is_some() {
+        if lines.next() != Some("<summary>Thinking</summary>") {
+            return Err(invalid("expected a thinking summary"));
+        }
+        let thinking = lines
+            .by_ref()
+            .take_while(|line| *line != "</details>")
+            .map(unescape_markdown)
+            .collect::<Vec<_>>();
+        message.thinking = Some(thinking.join("\n").trim_matches('\n').to_string());
+    }
+
+    let mut content = Vec::new();
+    while let Some(line) = lines.next() {
+        if let Some(url) = line
+            .strip_prefix("![image](")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            message
+                .images
+                .get_or_insert_with(Vec::new)
+                .push(parse_data_url(url)?);
+        } else if let Some(name) = line
+            .strip_prefix("**Tool call:** `")
+            .and_then(|rest| rest.strip_suffix('`'))
+        {
+            while lines.next_if_eq(&"").is_some() {}
+            if lines.next() != Some("```json") {
+                return Err(invalid(format!(
+                    "expected the arguments of tool call `{name}`"
+                )));
+            }
+            let arguments = lines
+                .by_ref()
+                .take_while(|line| *line != "```")
+                .collect::<Vec<_>>()
+                .join("\n");
+            message.tool_calls.push(ToolCall {
+                function: ToolCallFunction {
+                    name: name.to_string(),
+                    arguments: serde_json::from_str(&arguments)?,
+                },
+            });
+        } else {
+            content.push(unescape_markdown(line));
+        }
+    }
+    message.content = content.join("\n").trim_matches('\n').to_string();
+
+    Ok(message)
+}
+
+/// Writes conversations as a fine-tuning dataset in JSON Lines: one `{"messages": [...]}` object
+/// per conversation, with the messages in the OpenAI format (see [`to_openai`]).
+///
+/// The adapter trained on the dataset can then be added to a model with
+/// [`CreateModelRequest::adapters`](crate::models::create::CreateModelRequest::adapters).
+pub fn write_finetune_jsonl<'a>(
+    mut writer: impl Write,
+    conversations: impl IntoIterator<Item = &'a [ChatMessage]>,
+) -> Result<(), HistoryError> {
+    for messages in conversations {
+        let line = json!({ "messages": to_openai(messages) });
+        writeln!(writer, "{line}")?;
+    }
+
+    Ok(())
+}
+
+fn invalid(message: impl Into<String>) -> HistoryError {
+    HistoryError::InvalidTranscript(message.into())
+}
+
+fn string_field<'a>(value: &'a Value, field: &str) -> Result<&'a str, HistoryError> {
+    value
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid(format!("expected a string `{field}` field")))
+}
+
+/// A tool call whose arguments may be a JSON object or a string holding one.
+fn tool_call(name: &str, arguments: Option<&Value>) -> ToolCall {
+    let arguments = match arguments {
+        Some(Value::String(arguments)) => {
+            serde_json::from_str(arguments).unwrap_or_else(|_| json!(arguments))
+        }
+        Some(arguments) => arguments.clone(),
+        None => json!({}),
+    };
+
+    ToolCall {
+        function: ToolCallFunction {
+            name: name.to_string(),
+            arguments,
+        },
+    }
+}
+
+fn role_name(role: &MessageRole) -> &'static str {
+    match role {
+        MessageRole::System => "system",
+        MessageRole::User => "user",
+        MessageRole::Assistant => "assistant",
+        MessageRole::Tool => "tool",
+    }
+}
+
+/// The role of a Markdown role heading line.
+fn heading_role(line: &str) -> Option<MessageRole> {
+    match line.strip_prefix("## ")? {
+        "System" => Some(MessageRole::System),
+        "User" => Some(MessageRole::User),
+        "Assistant" => Some(MessageRole::Assistant),
+        "Tool" => Some(MessageRole::Tool),
+        _ => None,
+    }
+}
+
+/// Escapes the lines of `text` that [`from_markdown`] would read as markup, and those starting
+/// with the escape character itself, with a backslash, which Markdown renders as is.
+fn escape_markdown(text: &str) -> Cow<'_, str> {
+    let needs_escape = |line: &str| {
+        line.starts_with('\\')
+            || line.starts_with("![image](")
+            || line.starts_with("**Tool call:**")
+            || line == "<details>"
+            || line == "</details>"
+            || heading_role(line).is_some()
+    };
+    if !text.split('\n').any(needs_escape) {
+        return Cow::Borrowed(text);
+    }
+
+    let lines = text
+        .split('\n')
+        .map(|line| {
+            if needs_escape(line) {
+                Cow::Owned(format!("\\{line}"))
+            } else {
+                Cow::Borrowed(line)
+            }
+        })
+        .collect::<Vec<_>>();
+    Cow::Owned(lines.join("\n"))
+}
+
+fn unescape_markdown(line: &str) -> &str {
+    line.strip_prefix('\\').unwrap_or(line)
+}
+
+fn role_heading(role: &MessageRole) -> &'static str {
+    match role {
+        MessageRole::System => "System",
+        MessageRole::User => "User",
+        MessageRole::Assistant => "Assistant",
+        MessageRole::Tool => "Tool",
+    }
+}
+
+/// A data URL for an image, with its MIME type guessed from the first bytes.
+fn data_url(image: &Image) -> String {
+    let base64 = image.to_base64();
+    let mime_type = if base64.starts_with("/9j/") {
+        "image/jpeg"
+    } else if base64.starts_with("R0lGOD") {
+        "image/gif"
+    } else if base64.starts_with("UklGR") {
+        "image/webp"
+    } else {
+        "image/png"
+    };
+
+    format!("data:{mime_type};base64,{base64}")
+}
+
+fn parse_data_url(url: &str) -> Result<Image, HistoryError> {
+    url.strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .map(|(_, base64)| Image::from_base64(base64))
+        .ok_or_else(|| invalid("only base64 data URLs are supported for images"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Vec<ChatMessage> {
+        let mut call = ChatMessage::assistant(String::new());
+        call.thinking = Some("I should check the weather.".to_string());
+        call.tool_calls.push(tool_call(
+            "get_weather",
+            Some(&json!({ "city": "Paris", "unit": "celsius" })),
+        ));
+
+        vec![
+            ChatMessage::system("You are a helpful assistant.".to_string()),
+            ChatMessage::user("Should I take an umbrella?\nI'm in Paris.".to_string())
+                .add_image(Image::from_base64("iVBORw0KGgo="))
+                .add_image(Image::from_base64("/9j/4AAQ")),
+            call,
+            ChatMessage::tool("Rainy, 12°C".to_string()),
+            ChatMessage::assistant("Yes, it's raining in Paris.".to_string()),
+        ]
+    }
+
+    fn assert_same(actual: &[ChatMessage], expected: &[ChatMessage]) {
+        assert_eq!(
+            serde_json::to_value(actual).unwrap(),
+            serde_json::to_value(expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn openai_roundtrip() {
+        let messages = conversation();
+        let openai = to_openai(&messages);
+
+        assert_eq!(
+            openai[1]["content"][2]["image_url"]["url"],
+            "data:image/jpeg;base64,/9j/4AAQ"
+        );
+        assert_eq!(openai[2]["tool_calls"][0]["id"], "call_0");
+        assert_eq!(openai[3]["tool_call_id"], "call_0");
+        assert_same(&from_openai(&openai).unwrap(), &messages);
+    }
+
+    #[test]
+    fn sharegpt_roundtrip() {
+        let messages = conversation();
+        let sharegpt = to_sharegpt(&messages);
+
+        assert_eq!(sharegpt["conversations"][2]["from"], "gpt");
+        assert_eq!(sharegpt["conversations"][3]["from"], "function_call");
+        assert_eq!(sharegpt["images"].as_array().unwrap().len(), 2);
+        assert_same(&from_sharegpt(&sharegpt).unwrap(), &messages);
+    }
+
+    #[test]
+    fn markdown_roundtrip() {
+        let messages = conversation();
+        let markdown = to_markdown(&messages);
+
+        assert!(markdown.starts_with("## System\n\nYou are a helpful assistant.\n\n## User\n"));
+        assert_same(&from_markdown(&markdown).unwrap(), &messages);
+    }
+
+    #[test]
+    fn markup_in_contents_roundtrips() {
+        let mut answer = ChatMessage::assistant(
+            "## User\n![image](data:image/png;base64,iVBORw0KGgo=)\n**Tool call:** `x`\n\\n"
+                .to_string(),
+        );
+        answer.thinking = Some("</details>\n  indented".to_string());
+        let messages = vec![
+            ChatMessage::user("<details>\nHi".to_string()),
+            answer,
+            ChatMessage::assistant("  Indented after thinking".to_string()),
+        ];
+        let mut with_thinking = messages.clone();
+        with_thinking[2].thinking = Some(" Spaced ".to_string());
+
+        let markdown = to_markdown(&messages);
+        assert!(markdown.contains("\n\\## User\n\\![image]("));
+        assert_same(&from_markdown(&markdown).unwrap(), &messages);
+        assert_same(
+            &from_sharegpt(&to_sharegpt(&with_thinking)).unwrap(),
+            &with_thinking,
+        );
+    }
+
+    #[test]
+    fn finetune_export_writes_one_line_per_conversation() {
+        let messages = conversation();
+        let mut output = Vec::new();
+        write_finetune_jsonl(&mut output, [messages.as_slice(), &messages[..2]]).unwrap();
+
+        let lines = String::from_utf8(output).unwrap();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let second = serde_json::from_str::<Value>(lines[1]).unwrap();
+        assert_same(&from_openai(&second).unwrap(), &messages[..2]);
+    }
+
+    #[test]
+    fn invalid_transcripts_are_rejected() {
+        assert!(matches!(
+            from_openai(&json!([{ "role": "robot", "content": "beep" }])),
+            Err(HistoryError::InvalidTranscript(_))
+        ));
+        assert!(matches!(
+            from_sharegpt(&json!({ "conversations": [{ "from": "human", "value": "<image>Hi" }] })),
+            Err(HistoryError::InvalidTranscript(_))
+        ));
+        assert!(matches!(
+            from_markdown("Hello\n\n## User\n\nHi"),
+            Err(HistoryError::InvalidTranscript(_))
+        ));
+    }
+}
@@ -0,0 +1,408 @@
+//! A chat history that is a tree of messages, to edit earlier messages and regenerate answers
+//! without losing the previous branches.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::HistoryError,
+    generation::chat::{ChatMessage, MessageRole},
+};
+
+use super::ChatHistory;
+
+/// The ID of a message in a [`ConversationTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NodeId(usize);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    message: ChatMessage,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    /// The child on the path that was last active, to restore it when switching branches
+    active_child: Option<NodeId>,
+}
+
+/// A chat history where each message can have several alternative replies.
+///
+/// The active path, from a root message to the active leaf, is what the [`ChatHistory`]
+/// implementation exposes, so the tree can be used for requests like any other history. Pushed
+/// messages are appended to the active leaf.
+///
+/// ```ignore
+/// let mut tree = ConversationTree::new();
+/// ollama.send_chat_messages_with_history(&mut tree, request).await?;
+///
+/// // Edit the first message, which starts a new branch
+/// let first = tree.active_path()[0];
+/// tree.edit(first, "What about Mars?".to_string());
+/// ollama.send_chat_messages_with_history(&mut tree, ChatMessageRequest::new(model, vec![])).await?;
+///
+/// // Go back to the original branch
+/// tree.switch_to(first);
+/// ```
+///
+/// A deserialized tree is checked to be consistent, so that its IDs can't be out of range.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "RawConversationTree")]
+pub struct ConversationTree {
+    nodes: Vec<Node>,
+    roots: Vec<NodeId>,
+    /// The last message of the active path, or `None` if the next message starts a new root
+    active: Option<NodeId>,
+}
+
+/// A [`ConversationTree`] as it is serialized, before it is checked.
+#[derive(Deserialize)]
+struct RawConversationTree {
+    nodes: Vec<Node>,
+    roots: Vec<NodeId>,
+    active: Option<NodeId>,
+}
+
+impl TryFrom<RawConversationTree> for ConversationTree {
+    type Error = HistoryError;
+
+    fn try_from(raw: RawConversationTree) -> Result<Self, Self::Error> {
+        let invalid = |reason: String| Err(HistoryError::InvalidConversationTree(reason));
+        let len = raw.nodes.len();
+        let in_range = |id: NodeId| id.0 < len;
+
+        if let Some(active) = raw.active.filter(|&id| !in_range(id)) {
+            return invalid(format!("active message {} doesn't exist", active.0));
+        }
+        let mut linked = vec![false; len];
+        for &root in &raw.roots {
+            if !in_range(root) || raw.nodes[root.0].parent.is_some() {
+                return invalid(format!("message {} is not a root", root.0));
+            }
+            linked[root.0] = true;
+        }
+        for (index, node) in raw.nodes.iter().enumerate() {
+            // Parents are added before their children, which also rules out cycles
+            if node.parent.is_some_and(|parent| parent.0 >= index) {
+                return invalid(format!("message {index} has an invalid parent"));
+            }
+            for &child in &node.children {
+                if !in_range(child) || raw.nodes[child.0].parent != Some(NodeId(index)) {
+                    return invalid(format!("message {} is not a reply to {index}", child.0));
+                }
+                linked[child.0] = true;
+            }
+            if node
+                .active_child
+                .is_some_and(|child| !node.children.contains(&child))
+            {
+                return invalid(format!("message {index} has an invalid active reply"));
+            }
+        }
+        if let Some(index) = linked.iter().position(|linked| !linked) {
+            return invalid(format!("message {index} is not linked to its parent"));
+        }
+
+        Ok(Self {
+            nodes: raw.nodes,
+            roots: raw.roots,
+            active: raw.active,
+        })
+    }
+}
+
+impl ConversationTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the message with the given ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ID is not from this tree.
+    pub fn message(&self, id: NodeId) -> &ChatMessage {
+        &self.nodes[id.0].message
+    }
+
+    /// Returns the parent of a message, or `None` for a root message.
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].parent
+    }
+
+    /// Returns the replies to a message, in the order they were added.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id.0].children
+    }
+
+    /// Returns the alternatives of a message, including itself, in the order they were added.
+    pub fn siblings(&self, id: NodeId) -> &[NodeId] {
+        match self.nodes[id.0].parent {
+            Some(parent) => &self.nodes[parent.0].children,
+            None => &self.roots,
+        }
+    }
+
+    /// Returns the first message of each root branch.
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// Returns the last message of the active path.
+    pub fn active_leaf(&self) -> Option<NodeId> {
+        self.active
+    }
+
+    /// Returns the IDs of the messages of the active path, from the root.
+    pub fn active_path(&self) -> Vec<NodeId> {
+        let mut path = Vec::new();
+        let mut current = self.active;
+        while let Some(id) = current {
+            path.push(id);
+            current = self.nodes[id.0].parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Returns the number of messages in the tree, across all branches.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds `message` as an alternative to the message `at`, and makes it the active leaf.
+    ///
+    /// The branch of `at` is kept, and can be switched back to with [`Self::switch_to`].
+    pub fn fork(&mut self, at: NodeId, message: ChatMessage) -> NodeId {
+        let parent = self.nodes[at.0].parent;
+        self.add(parent, message)
+    }
+
+    /// Forks at the message `at` with its content replaced by `content`.
+    pub fn edit(&mut self, at: NodeId, content: String) -> NodeId {
+        let mut message = self.nodes[at.0].message.clone();
+        message.content = content;
+        self.fork(at, message)
+    }
+
+    /// Makes the path through `id` active, down to the leaf that was last active below it.
+    pub fn switch_to(&mut self, id: NodeId) {
+        let mut leaf = id;
+        while let Some(child) = self.nodes[leaf.0].active_child {
+            leaf = child;
+        }
+        self.activate(Some(leaf));
+    }
+
+    /// Prepares the regeneration of the last assistant message of the active path: the active
+    /// leaf is moved to the message it answered, so that the next response is added as an
+    /// alternative to it. Tool results after the assistant message are left out with it.
+    ///
+    /// Returns the assistant message that will be replaced, or `None` if there is no answer to
+    /// regenerate, in which case the tree is left as is: either the active path has no assistant
+    /// message, or it ends with user or system messages that aren't answered yet.
+    pub fn regenerate(&mut self) -> Option<NodeId> {
+        let last_assistant = self
+            .active_path()
+            .into_iter()
+            .rev()
+            .find(|id| self.nodes[id.0].message.role != MessageRole::Tool)
+            .filter(|id| self.nodes[id.0].message.role == MessageRole::Assistant)?;
+
+        // For a root message, the response starts a new root
+        self.activate(self.nodes[last_assistant.0].parent);
+        Some(last_assistant)
+    }
+
+    fn add(&mut self, parent: Option<NodeId>, message: ChatMessage) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            message,
+            parent,
+            children: Vec::new(),
+            active_child: None,
+        });
+
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+
+        self.activate(Some(id));
+        id
+    }
+
+    /// Makes `leaf` the active leaf, and remembers the path to it.
+    fn activate(&mut self, leaf: Option<NodeId>) {
+        self.active = leaf;
+
+        let Some(mut current) = leaf else {
+            return;
+        };
+        // The path below the new leaf is no longer the active one
+        self.nodes[current.0].active_child = None;
+        while let Some(parent) = self.nodes[current.0].parent {
+            self.nodes[parent.0].active_child = Some(current);
+            current = parent;
+        }
+    }
+}
+
+impl ChatHistory for ConversationTree {
+    fn push(&mut self, message: ChatMessage) {
+        self.add(self.active, message);
+    }
+
+    fn messages(&self) -> Cow<'_, [ChatMessage]> {
+        Cow::Owned(
+            self.active_path()
+                .into_iter()
+                .map(|id| self.nodes[id.0].message.clone())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(tree: &ConversationTree) -> Vec<String> {
+        tree.messages()
+            .iter()
+            .map(|message| message.content.clone())
+            .collect()
+    }
+
+    #[test]
+    fn edits_and_regenerations_create_branches() {
+        let mut tree = ConversationTree::new();
+        tree.push(ChatMessage::user("Hi".to_string()));
+        tree.push(ChatMessage::assistant("Hello!".to_string()));
+        tree.push(ChatMessage::user("Tell me a joke".to_string()));
+        tree.push(ChatMessage::assistant("Knock knock".to_string()));
+
+        let question = tree.active_path()[2];
+        let original_answer = tree.active_path()[3];
+
+        assert_eq!(tree.regenerate(), Some(original_answer));
+        assert_eq!(tree.active_leaf(), Some(question));
+        tree.push(ChatMessage::assistant("Why did the chicken...".to_string()));
+        assert_eq!(tree.siblings(original_answer).len(), 2);
+        assert_eq!(
+            contents(&tree),
+            ["Hi", "Hello!", "Tell me a joke", "Why did the chicken..."]
+        );
+
+        let edited = tree.edit(question, "Tell me a story".to_string());
+        assert_eq!(contents(&tree), ["Hi", "Hello!", "Tell me a story"]);
+        tree.push(ChatMessage::assistant("Once upon a time".to_string()));
+
+        tree.switch_to(question);
+        assert_eq!(
+            contents(&tree),
+            ["Hi", "Hello!", "Tell me a joke", "Why did the chicken..."]
+        );
+
+        tree.switch_to(original_answer);
+        assert_eq!(
+            contents(&tree),
+            ["Hi", "Hello!", "Tell me a joke", "Knock knock"]
+        );
+
+        tree.switch_to(edited);
+        assert_eq!(
+            contents(&tree),
+            ["Hi", "Hello!", "Tell me a story", "Once upon a time"]
+        );
+        assert_eq!(tree.len(), 7);
+    }
+
+    #[test]
+    fn regenerate_keeps_unanswered_messages() {
+        let mut tree = ConversationTree::new();
+        tree.push(ChatMessage::user("Hi".to_string()));
+        tree.push(ChatMessage::assistant("Hello!".to_string()));
+        tree.push(ChatMessage::user("Tell me a joke".to_string()));
+
+        assert_eq!(tree.regenerate(), None);
+        assert_eq!(contents(&tree), ["Hi", "Hello!", "Tell me a joke"]);
+
+        let mut empty = ConversationTree::new();
+        assert_eq!(empty.regenerate(), None);
+    }
+
+    #[test]
+    fn regenerate_replaces_root_answers() {
+        let mut tree = ConversationTree::new();
+        tree.push(ChatMessage::assistant("Hello! How can I help?".to_string()));
+        let greeting = tree.active_path()[0];
+
+        assert_eq!(tree.regenerate(), Some(greeting));
+        assert_eq!(tree.active_leaf(), None);
+        tree.push(ChatMessage::assistant("Hi there!".to_string()));
+        assert_eq!(tree.roots().len(), 2);
+        assert_eq!(contents(&tree), ["Hi there!"]);
+    }
+
+    #[test]
+    fn regenerate_leaves_out_tool_results() {
+        let mut tree = ConversationTree::new();
+        tree.push(ChatMessage::user("Weather in Paris?".to_string()));
+        tree.push(ChatMessage::assistant(String::new()));
+        tree.push(ChatMessage::tool("Sunny".to_string()));
+        let [question, call, _] = tree.active_path()[..] else {
+            panic!("expected three messages");
+        };
+
+        assert_eq!(tree.regenerate(), Some(call));
+        assert_eq!(tree.active_leaf(), Some(question));
+    }
+
+    #[test]
+    fn tree_roundtrips_through_json() {
+        let mut tree = ConversationTree::new();
+        tree.push(ChatMessage::user("Hi".to_string()));
+        tree.push(ChatMessage::assistant("Hello!".to_string()));
+        let first = tree.active_path()[0];
+        tree.edit(first, "Hey".to_string());
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let mut restored = serde_json::from_str::<ConversationTree>(&json).unwrap();
+        assert_eq!(contents(&restored), ["Hey"]);
+
+        restored.switch_to(first);
+        assert_eq!(contents(&restored), ["Hi", "Hello!"]);
+    }
+
+    #[test]
+    fn inconsistent_trees_are_rejected() {
+        let message = serde_json::to_value(ChatMessage::user("Hi".to_string())).unwrap();
+        let node = |parent: Option<usize>, children: Vec<usize>| {
+            serde_json::json!({
+                "message": message,
+                "parent": parent,
+                "children": children,
+                "active_child": null,
+            })
+        };
+
+        for tree in [
+            serde_json::json!({ "nodes": [node(None, vec![])], "roots": [0], "active": 3 }),
+            serde_json::json!({ "nodes": [node(None, vec![5])], "roots": [0], "active": 0 }),
+            serde_json::json!({ "nodes": [node(None, vec![])], "roots": [], "active": null }),
+            serde_json::json!({
+                "nodes": [node(Some(1), vec![1]), node(Some(0), vec![0])],
+                "roots": [],
+                "active": null,
+            }),
+        ] {
+            let error = serde_json::from_value::<ConversationTree>(tree).unwrap_err();
+            assert!(error.to_string().starts_with("Invalid conversation tree"));
+        }
+    }
+}
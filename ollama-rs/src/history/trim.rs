@@ -0,0 +1,477 @@
+//! Strategies to trim the chat history sent to the model, so that long chats fit in the context
+//! window.
+//!
+//! The history itself is kept whole: only the messages of each request are trimmed.
+
+use std::borrow::Cow;
+
+use crate::{
+    generation::chat::{ChatMessage, MessageRole},
+    models::ModelOptions,
+};
+
+use super::ChatHistory;
+
+/// Estimates the number of tokens of a message.
+///
+/// Tokenizers differ from model to model, so estimates should err on the high side.
+pub trait TokenEstimator: Send + Sync {
+    fn estimate(&self, message: &ChatMessage) -> usize;
+}
+
+impl<F: Fn(&ChatMessage) -> usize + Send + Sync> TokenEstimator for F {
+    fn estimate(&self, message: &ChatMessage) -> usize {
+        self(message)
+    }
+}
+
+/// Estimates tokens from the number of characters of a message, which is about 4 characters
+/// per token for English text.
+#[derive(Debug, Clone, Copy)]
+pub struct CharTokenEstimator {
+    chars_per_token: f32,
+    tokens_per_message: usize,
+}
+
+impl CharTokenEstimator {
+    /// Creates an estimator counting `chars_per_token` characters per token.
+    pub fn new(chars_per_token: f32) -> Self {
+        Self {
+            chars_per_token,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the number of tokens added to each message for its role and formatting. (Default: 4)
+    pub fn tokens_per_message(mut self, tokens_per_message: usize) -> Self {
+        self.tokens_per_message = tokens_per_message;
+        self
+    }
+}
+
+impl Default for CharTokenEstimator {
+    fn default() -> Self {
+        Self {
+            chars_per_token: 4.0,
+            tokens_per_message: 4,
+        }
+    }
+}
+
+impl TokenEstimator for CharTokenEstimator {
+    fn estimate(&self, message: &ChatMessage) -> usize {
+        let mut chars = message.content.chars().count();
+        if let Some(thinking) = &message.thinking {
+            chars += thinking.chars().count();
+        }
+        for call in &message.tool_calls {
+            chars += call.function.name.len() + call.function.arguments.to_string().len();
+        }
+
+        (chars as f32 / self.chars_per_token).ceil() as usize + self.tokens_per_message
+    }
+}
+
+/// A strategy to trim the messages sent to the model.
+pub trait TrimStrategy: Send + Sync {
+    /// Trims `messages` so that they fit in `max_tokens`, as estimated by `estimator`.
+    fn trim(
+        &self,
+        messages: Vec<ChatMessage>,
+        max_tokens: usize,
+        estimator: &dyn TokenEstimator,
+    ) -> Vec<ChatMessage>;
+
+    /// Whether this strategy needs the token limit. Strategies that don't are given
+    /// `usize::MAX`, and the context length of the model is never looked up.
+    fn uses_token_limit(&self) -> bool {
+        true
+    }
+}
+
+/// Keeps the system messages at the start of the history and the last `max_messages` messages.
+#[derive(Debug, Clone, Copy)]
+pub struct SlidingWindow {
+    max_messages: usize,
+}
+
+impl SlidingWindow {
+    pub fn new(max_messages: usize) -> Self {
+        Self { max_messages }
+    }
+}
+
+impl TrimStrategy for SlidingWindow {
+    fn trim(
+        &self,
+        mut messages: Vec<ChatMessage>,
+        _max_tokens: usize,
+        _estimator: &dyn TokenEstimator,
+    ) -> Vec<ChatMessage> {
+        let system = leading_system_messages(&messages);
+        let start = messages.len().saturating_sub(self.max_messages).max(system);
+        messages.drain(system..start);
+        drop_orphan_tool_results(messages, system)
+    }
+
+    fn uses_token_limit(&self) -> bool {
+        false
+    }
+}
+
+/// Keeps the system messages at the start of the history and as many of the last messages as
+/// fit in the token limit. The last message is always kept.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeepLastTokens;
+
+impl TrimStrategy for KeepLastTokens {
+    fn trim(
+        &self,
+        mut messages: Vec<ChatMessage>,
+        max_tokens: usize,
+        estimator: &dyn TokenEstimator,
+    ) -> Vec<ChatMessage> {
+        let system = leading_system_messages(&messages);
+        let mut tokens = messages[..system]
+            .iter()
+            .map(|message| estimator.estimate(message))
+            .sum::<usize>();
+
+        let mut start = messages.len();
+        while start > system {
+            let message_tokens = estimator.estimate(&messages[start - 1]);
+            if start < messages.len() && tokens + message_tokens > max_tokens {
+                break;
+            }
+            tokens += message_tokens;
+            start -= 1;
+        }
+
+        messages.drain(system..start);
+        drop_orphan_tool_results(messages, system)
+    }
+}
+
+/// Drops tool calls and tool results, oldest first, before dropping anything else. The tool
+/// chatter of the last turn is kept, as the model may still need it to answer.
+///
+/// The tool calls of an assistant message are dropped along with their results, and the
+/// message itself is kept if it also has content. If the messages still don't fit once the tool
+/// chatter is gone, they are trimmed with [`KeepLastTokens`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DropToolChatterFirst;
+
+impl TrimStrategy for DropToolChatterFirst {
+    fn trim(
+        &self,
+        messages: Vec<ChatMessage>,
+        max_tokens: usize,
+        estimator: &dyn TokenEstimator,
+    ) -> Vec<ChatMessage> {
+        let mut tokens = messages
+            .iter()
+            .map(|message| estimator.estimate(message))
+            .sum::<usize>();
+        let last_turn = messages
+            .iter()
+            .rposition(|message| message.role == MessageRole::User)
+            .unwrap_or(0);
+
+        let mut kept = Vec::with_capacity(messages.len());
+        let mut messages = messages.into_iter().enumerate().peekable();
+        while let Some((i, mut message)) = messages.next() {
+            if tokens <= max_tokens || i >= last_turn {
+                kept.push(message);
+                continue;
+            }
+
+            match message.role {
+                MessageRole::Assistant if !message.tool_calls.is_empty() => {
+                    tokens = tokens.saturating_sub(estimator.estimate(&message));
+                    message.tool_calls.clear();
+                    while let Some((_, result)) =
+                        messages.next_if(|(_, next)| next.role == MessageRole::Tool)
+                    {
+                        tokens = tokens.saturating_sub(estimator.estimate(&result));
+                    }
+
+                    if !message.content.trim().is_empty() {
+                        tokens += estimator.estimate(&message);
+                        kept.push(message);
+                    }
+                }
+                // A result whose call is already gone
+                MessageRole::Tool => tokens = tokens.saturating_sub(estimator.estimate(&message)),
+                _ => kept.push(message),
+            }
+        }
+
+        if tokens > max_tokens {
+            KeepLastTokens.trim(kept, max_tokens, estimator)
+        } else {
+            kept
+        }
+    }
+}
+
+fn leading_system_messages(messages: &[ChatMessage]) -> usize {
+    messages
+        .iter()
+        .take_while(|message| message.role == MessageRole::System)
+        .count()
+}
+
+/// Tool results at the start of the trimmed messages have lost the call they answer.
+fn drop_orphan_tool_results(mut messages: Vec<ChatMessage>, start: usize) -> Vec<ChatMessage> {
+    let orphans = messages[start..]
+        .iter()
+        .take_while(|message| message.role == MessageRole::Tool)
+        .count();
+    // The last message is always kept
+    let orphans = orphans.min(messages.len().saturating_sub(start + 1));
+    messages.drain(start..start + orphans);
+    messages
+}
+
+/// The trimming configuration of a history: the strategy, the token estimator and the token
+/// limit.
+///
+/// The token limit is, in order of precedence, the one set with [`Trimming::token_limit`], the
+/// `num_ctx` option of the request, or the context length of the model, which is looked up once
+/// with `/api/show` and then cached.
+pub struct Trimming {
+    strategy: Box<dyn TrimStrategy>,
+    estimator: Box<dyn TokenEstimator>,
+    token_limit: Option<usize>,
+    reserved_tokens: Option<usize>,
+    context_length: Option<(String, usize)>,
+}
+
+impl Trimming {
+    pub fn new(strategy: impl TrimStrategy + 'static) -> Self {
+        Self {
+            strategy: Box::new(strategy),
+            estimator: Box::new(CharTokenEstimator::default()),
+            token_limit: None,
+            reserved_tokens: None,
+            context_length: None,
+        }
+    }
+
+    /// Sets the token estimator. (Default: [`CharTokenEstimator`])
+    pub fn estimator(mut self, estimator: impl TokenEstimator + 'static) -> Self {
+        self.estimator = Box::new(estimator);
+        self
+    }
+
+    /// Sets the token limit instead of taking it from the request options or the model.
+    pub fn token_limit(mut self, token_limit: usize) -> Self {
+        self.token_limit = Some(token_limit);
+        self
+    }
+
+    /// Sets the number of tokens left for the response. (Default: a quarter of the token limit)
+    pub fn reserved_tokens(mut self, reserved_tokens: usize) -> Self {
+        self.reserved_tokens = Some(reserved_tokens);
+        self
+    }
+
+    /// Whether the context length of `model` must be looked up before trimming.
+    pub(crate) fn needs_context_length(&self, model: &str, options: Option<&ModelOptions>) -> bool {
+        self.strategy.uses_token_limit()
+            && self.token_limit.is_none()
+            && options.and_then(|options| options.num_ctx).is_none()
+            && !matches!(&self.context_length, Some((cached, _)) if cached == model)
+    }
+
+    pub(crate) fn set_context_length(&mut self, model: &str, context_length: usize) {
+        self.context_length = Some((model.to_string(), context_length));
+    }
+
+    /// Trims `messages` for a request to `model` with `options`.
+    pub(crate) fn trim(
+        &self,
+        messages: Vec<ChatMessage>,
+        model: &str,
+        options: Option<&ModelOptions>,
+    ) -> Vec<ChatMessage> {
+        let max_tokens = if self.strategy.uses_token_limit() {
+            let token_limit = self
+                .token_limit
+                .or_else(|| {
+                    options
+                        .and_then(|options| options.num_ctx)
+                        .map(|n| n as usize)
+                })
+                .or_else(|| match &self.context_length {
+                    Some((cached, context_length)) if cached == model => Some(*context_length),
+                    _ => None,
+                });
+            let Some(token_limit) = token_limit else {
+                return messages;
+            };
+            let reserved = self.reserved_tokens.unwrap_or(token_limit / 4);
+            token_limit.saturating_sub(reserved)
+        } else {
+            usize::MAX
+        };
+
+        self.strategy
+            .trim(messages, max_tokens, self.estimator.as_ref())
+    }
+}
+
+/// A [`ChatHistory`] whose messages are trimmed before each request.
+///
+/// ```ignore
+/// let history = TrimmedHistory::new(vec![], Trimming::new(DropToolChatterFirst));
+/// let coordinator = Coordinator::new(ollama, model, history);
+/// ```
+pub struct TrimmedHistory<C = Vec<ChatMessage>> {
+    inner: C,
+    trimming: Trimming,
+}
+
+impl<C: ChatHistory> TrimmedHistory<C> {
+    pub fn new(inner: C, trimming: Trimming) -> Self {
+        Self { inner, trimming }
+    }
+
+    /// Returns a reference to the wrapped history.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped history.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the wrapped history.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: ChatHistory> ChatHistory for TrimmedHistory<C> {
+    fn push(&mut self, message: ChatMessage) {
+        self.inner.push(message);
+    }
+
+    fn messages(&self) -> Cow<'_, [ChatMessage]> {
+        self.inner.messages()
+    }
+
+    fn trimming(&mut self) -> Option<&mut Trimming> {
+        Some(&mut self.trimming)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::tools::{ToolCall, ToolCallFunction};
+
+    /// One token per message, to make budgets easy to follow.
+    fn one_token(_: &ChatMessage) -> usize {
+        1
+    }
+
+    fn tool_call() -> ChatMessage {
+        let mut message = ChatMessage::assistant(String::new());
+        message.tool_calls.push(ToolCall {
+            function: ToolCallFunction {
+                name: "search".to_string(),
+                arguments: serde_json::json!({}),
+            },
+        });
+        message
+    }
+
+    fn contents(messages: &[ChatMessage]) -> Vec<&str> {
+        messages
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn sliding_window_keeps_system_prompt() {
+        let messages = vec![
+            ChatMessage::system("system".to_string()),
+            ChatMessage::user("1".to_string()),
+            ChatMessage::assistant("2".to_string()),
+            ChatMessage::user("3".to_string()),
+            ChatMessage::assistant("4".to_string()),
+        ];
+
+        let trimmed = SlidingWindow::new(2).trim(messages, usize::MAX, &one_token);
+        assert_eq!(contents(&trimmed), ["system", "3", "4"]);
+    }
+
+    #[test]
+    fn keep_last_tokens_drops_orphan_tool_results() {
+        let messages = vec![
+            ChatMessage::system("system".to_string()),
+            ChatMessage::user("1".to_string()),
+            tool_call(),
+            ChatMessage::tool("result".to_string()),
+            ChatMessage::assistant("2".to_string()),
+            ChatMessage::user("3".to_string()),
+        ];
+
+        let trimmed = KeepLastTokens.trim(messages, 4, &one_token);
+        assert_eq!(contents(&trimmed), ["system", "2", "3"]);
+    }
+
+    #[test]
+    fn tool_chatter_is_dropped_first() {
+        let messages = vec![
+            ChatMessage::user("1".to_string()),
+            tool_call(),
+            ChatMessage::tool("old result".to_string()),
+            ChatMessage::assistant("2".to_string()),
+            ChatMessage::user("3".to_string()),
+            tool_call(),
+            ChatMessage::tool("new result".to_string()),
+        ];
+
+        let trimmed = DropToolChatterFirst.trim(messages, 5, &one_token);
+        assert_eq!(contents(&trimmed), ["1", "2", "3", "", "new result"]);
+    }
+
+    #[test]
+    fn tool_calls_are_dropped_with_their_results() {
+        let mut answer_with_calls = tool_call();
+        answer_with_calls.content = "Let me search".to_string();
+        answer_with_calls.tool_calls.extend(tool_call().tool_calls);
+        let messages = vec![
+            ChatMessage::user("1".to_string()),
+            answer_with_calls,
+            ChatMessage::tool("first result".to_string()),
+            ChatMessage::tool("second result".to_string()),
+            ChatMessage::assistant("2".to_string()),
+            ChatMessage::user("3".to_string()),
+        ];
+
+        // Dropping the first result would be enough, but the call goes with all its results
+        let trimmed = DropToolChatterFirst.trim(messages, 5, &one_token);
+        assert_eq!(contents(&trimmed), ["1", "Let me search", "2", "3"]);
+        assert!(trimmed[1].tool_calls.is_empty());
+    }
+
+    #[test]
+    fn token_limit_comes_from_options() {
+        let messages = (0..10)
+            .map(|i| ChatMessage::user(i.to_string()))
+            .collect::<Vec<_>>();
+        let trimming = Trimming::new(KeepLastTokens)
+            .estimator(one_token)
+            .reserved_tokens(1);
+        let options = ModelOptions::default().num_ctx(4);
+
+        assert!(!trimming.needs_context_length("model", Some(&options)));
+        let trimmed = trimming.trim(messages, "model", Some(&options));
+        assert_eq!(contents(&trimmed), ["7", "8", "9"]);
+    }
+}
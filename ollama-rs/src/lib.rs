@@ -1,9 +1,13 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+use std::collections::HashMap;
+
 use url::Url;
 
+use crate::generation::defaults::{ModelKey, RequestDefaults};
+
 #[cfg(feature = "macros")]
-pub use ollama_rs_macros::function;
+pub use ollama_rs_macros::{function, tools};
 
 #[cfg(feature = "macros")]
 pub mod re_exports {
@@ -19,6 +23,11 @@ pub mod generation;
 pub mod headers;
 pub mod history;
 pub mod models;
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub mod session;
+#[cfg(all(test, feature = "tokio"))]
+mod test_util;
 
 /// A trait to try to convert some type into a [`Url`].
 ///
@@ -92,6 +101,8 @@ pub struct Ollama {
     pub(crate) reqwest_client: reqwest::Client,
     #[cfg(feature = "headers")]
     pub(crate) request_headers: reqwest::header::HeaderMap,
+    pub(crate) defaults: RequestDefaults,
+    pub(crate) model_defaults: HashMap<ModelKey, RequestDefaults>,
 }
 
 /// The main struct representing an Ollama client.
@@ -174,6 +185,8 @@ impl Ollama {
             reqwest_client,
             #[cfg(feature = "headers")]
             request_headers: reqwest::header::HeaderMap::new(),
+            defaults: RequestDefaults::default(),
+            model_defaults: HashMap::new(),
         }
     }
 
@@ -248,6 +261,8 @@ impl Default for Ollama {
             reqwest_client: reqwest::Client::new(),
             #[cfg(feature = "headers")]
             request_headers: reqwest::header::HeaderMap::new(),
+            defaults: RequestDefaults::default(),
+            model_defaults: HashMap::new(),
         }
     }
 }
@@ -274,6 +289,8 @@ pub struct OllamaBuilder {
     reqwest_client: Option<reqwest::Client>,
     #[cfg(feature = "headers")]
     request_headers: reqwest::header::HeaderMap,
+    defaults: RequestDefaults,
+    model_defaults: HashMap<ModelKey, RequestDefaults>,
 }
 
 impl OllamaBuilder {
@@ -332,6 +349,24 @@ impl OllamaBuilder {
         self
     }
 
+    /// Sets the options, keep alive and thinking applied to every request that doesn't set
+    /// them. See [`RequestDefaults`] for how they are merged.
+    pub fn defaults(mut self, defaults: RequestDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Sets the defaults of the requests to `model`, which win over the defaults of the client
+    /// and lose to the values of the request.
+    ///
+    /// Equivalent names, such as `qwen3` and `qwen3:latest`, share the same defaults, so the
+    /// last ones set win.
+    pub fn model_defaults(mut self, model: impl Into<String>, defaults: RequestDefaults) -> Self {
+        self.model_defaults
+            .insert(ModelKey::new(&model.into()), defaults);
+        self
+    }
+
     /// Consumes the builder and returns a configured [`Ollama`] instance.
     pub fn build(self) -> Ollama {
         Ollama {
@@ -339,6 +374,8 @@ impl OllamaBuilder {
             reqwest_client: self.reqwest_client.unwrap_or_default(),
             #[cfg(feature = "headers")]
             request_headers: self.request_headers,
+            defaults: self.defaults,
+            model_defaults: self.model_defaults,
         }
     }
 }
@@ -352,6 +389,8 @@ impl Default for OllamaBuilder {
             reqwest_client: None,
             #[cfg(feature = "headers")]
             request_headers: reqwest::header::HeaderMap::new(),
+            defaults: RequestDefaults::default(),
+            model_defaults: HashMap::new(),
         }
     }
 }
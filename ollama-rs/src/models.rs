@@ -6,8 +6,11 @@ pub mod copy;
 pub mod create;
 pub mod delete;
 pub mod list_local;
+pub mod name;
+mod parameters;
 pub mod pull;
 pub mod push;
+pub mod reconcile;
 pub mod show_info;
 
 use std::collections::HashMap;
@@ -27,8 +30,59 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalModel {
     pub name: String,
+    /// The time the model was last modified, such as `2024-06-04T14:38:31.83753-07:00`.
     pub modified_at: String,
+    /// The size of the model, in bytes.
     pub size: u64,
+    /// The SHA-256 digest of the manifest of the model.
+    #[serde(default)]
+    pub digest: String,
+    #[serde(default)]
+    pub details: ModelDetails,
+}
+
+impl LocalModel {
+    /// Parses [`Self::modified_at`], or returns `None` if it isn't an RFC 3339 timestamp.
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    #[cfg(feature = "chrono")]
+    pub fn modified_at_datetime(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        chrono::DateTime::parse_from_rfc3339(&self.modified_at).ok()
+    }
+}
+
+/// The format, family and quantization of a model.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelDetails {
+    /// The model this model was created from, if any.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub parent_model: String,
+    /// The format of the weights, such as `gguf`.
+    #[serde(default)]
+    pub format: String,
+    /// The family of the model, such as `llama`.
+    #[serde(default)]
+    pub family: String,
+    /// All the families of the model, including the families of its projectors.
+    #[serde(default)]
+    pub families: Option<Vec<String>>,
+    /// The number of parameters of the model, such as `8.0B`.
+    #[serde(default)]
+    pub parameter_size: String,
+    /// The quantization of the weights, such as `Q4_K_M`.
+    #[serde(default)]
+    pub quantization_level: String,
+}
+
+/// A tensor of a model, listed by [`Ollama::show_model_info_verbose`].
+///
+/// [`Ollama::show_model_info_verbose`]: crate::Ollama::show_model_info_verbose
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TensorInfo {
+    pub name: String,
+    /// The type of the elements, such as `F32` or `Q4_K`.
+    #[serde(rename = "type")]
+    pub tensor_type: String,
+    pub shape: Vec<u64>,
 }
 
 /// Represents information about a model.
@@ -54,51 +108,175 @@ pub struct ModelInfo {
     pub parameters: String,
     #[serde(default = "String::new")]
     pub template: String,
+    #[serde(default = "String::new")]
+    pub system: String,
     #[serde(default = "serde_json::Map::new")]
     pub model_info: serde_json::Map<String, serde_json::Value>,
     #[serde(default = "Vec::new")]
     pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub details: ModelDetails,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<String>,
+    /// The metadata of the projector of a multimodal model, such as `clip.vision.image_size`.
+    #[serde(default = "serde_json::Map::new")]
+    pub projector_info: serde_json::Map<String, serde_json::Value>,
+    /// The tensors of the model, only listed by [`Ollama::show_model_info_verbose`].
+    ///
+    /// [`Ollama::show_model_info_verbose`]: crate::Ollama::show_model_info_verbose
+    #[serde(default = "Vec::new")]
+    pub tensors: Vec<TensorInfo>,
+}
+
+impl ModelInfo {
+    /// Parses [`Self::modified_at`], or returns `None` if it is missing or isn't an RFC 3339
+    /// timestamp.
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    #[cfg(feature = "chrono")]
+    pub fn modified_at_datetime(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        chrono::DateTime::parse_from_rfc3339(self.modified_at.as_deref()?).ok()
+    }
+
+    /// The architecture of the model, such as `llama`, read from `general.architecture` in
+    /// `model_info`.
+    pub fn architecture(&self) -> Option<&str> {
+        self.model_info.get("general.architecture")?.as_str()
+    }
+
+    /// The number of parameters of the model, read from `general.parameter_count` in
+    /// `model_info`.
+    pub fn parameter_count(&self) -> Option<u64> {
+        self.model_info.get("general.parameter_count")?.as_u64()
+    }
+
+    /// The context length the model was trained with, read from `<architecture>.context_length`
+    /// in `model_info`.
+    pub fn context_length(&self) -> Option<u64> {
+        self.architecture_info("context_length")?.as_u64()
+    }
+
+    /// The size of the embeddings of the model, read from `<architecture>.embedding_length` in
+    /// `model_info`.
+    pub fn embedding_length(&self) -> Option<u64> {
+        self.architecture_info("embedding_length")?.as_u64()
+    }
+
+    /// The quantization of the weights of the model, such as `Q4_K_M`, read from
+    /// `general.file_type` in `model_info`.
+    pub fn quantization(&self) -> Option<&'static str> {
+        let file_type = self.model_info.get("general.file_type")?.as_u64()?;
+        let quantization = match file_type {
+            0 => "F32",
+            1 => "F16",
+            2 => "Q4_0",
+            3 => "Q4_1",
+            7 => "Q8_0",
+            8 => "Q5_0",
+            9 => "Q5_1",
+            10 => "Q2_K",
+            11 => "Q3_K_S",
+            12 => "Q3_K_M",
+            13 => "Q3_K_L",
+            14 => "Q4_K_S",
+            15 => "Q4_K_M",
+            16 => "Q5_K_S",
+            17 => "Q5_K_M",
+            18 => "Q6_K",
+            19 => "IQ2_XXS",
+            20 => "IQ2_XS",
+            21 => "Q2_K_S",
+            22 => "IQ3_XS",
+            23 => "IQ3_XXS",
+            24 => "IQ1_S",
+            25 => "IQ4_NL",
+            26 => "IQ3_S",
+            27 => "IQ3_M",
+            28 => "IQ2_S",
+            29 => "IQ2_M",
+            30 => "IQ4_XS",
+            31 => "IQ1_M",
+            32 => "BF16",
+            36 => "TQ1_0",
+            37 => "TQ2_0",
+            _ => return None,
+        };
+        Some(quantization)
+    }
+
+    /// The value of `<architecture>.<key>` in `model_info`.
+    fn architecture_info(&self, key: &str) -> Option<&serde_json::Value> {
+        let architecture = self.architecture()?;
+        self.model_info.get(&format!("{architecture}.{key}"))
+    }
 }
 
-// Options for generation requests to Ollama.
+/// Options for generation requests to Ollama.
+///
+/// The values are read through the fields, and set with the builder methods of the same names.
+/// The setters don't check the values: use [`ModelOptions::validate`] to find the values the
+/// server would reject or misinterpret.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct ModelOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) mirostat: Option<u8>,
+    pub mirostat: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirostat_eta: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirostat_tau: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_gqa: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_gpu: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_thread: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_last_n: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) mirostat_eta: Option<f32>,
+    pub temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) mirostat_tau: Option<f32>,
+    pub seed: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) num_ctx: Option<u64>,
+    pub stop: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) num_gqa: Option<u32>,
+    pub tfs_z: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) num_gpu: Option<u32>,
+    pub num_predict: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) num_thread: Option<u32>,
+    pub top_k: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) repeat_last_n: Option<i32>,
+    pub top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) repeat_penalty: Option<f32>,
+    pub min_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) temperature: Option<f32>,
+    pub num_keep: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) seed: Option<i32>,
+    pub typical_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) stop: Option<Vec<String>>,
+    pub presence_penalty: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) tfs_z: Option<f32>,
+    pub frequency_penalty: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) num_predict: Option<i32>,
+    pub penalize_newline: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) top_k: Option<u32>,
+    pub num_batch: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) top_p: Option<f32>,
+    pub main_gpu: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(super) min_p: Option<f32>,
+    pub low_vram: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vocab_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_mmap: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_mlock: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numa: Option<bool>,
     #[serde(flatten, skip_serializing_if = "HashMap::is_empty")]
-    pub(super) extra: HashMap<String, serde_json::Value>,
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 impl ModelOptions {
@@ -204,6 +382,78 @@ impl ModelOptions {
         self
     }
 
+    /// Number of tokens of the prompt to keep when the context is full and older tokens are dropped. (Default: 4, -1 = all)
+    pub fn num_keep(mut self, num_keep: i32) -> Self {
+        self.num_keep = Some(num_keep);
+        self
+    }
+
+    /// Locally typical sampling: tokens are sampled among those whose information content is close to the expected one, keeping this cumulative probability. (Default: 1.0, 1.0 = disabled)
+    pub fn typical_p(mut self, typical_p: f32) -> Self {
+        self.typical_p = Some(typical_p);
+        self
+    }
+
+    /// Penalizes tokens that already appeared in the text, regardless of how often, to encourage new topics. (Default: 0.0)
+    pub fn presence_penalty(mut self, presence_penalty: f32) -> Self {
+        self.presence_penalty = Some(presence_penalty);
+        self
+    }
+
+    /// Penalizes tokens in proportion to how often they already appeared in the text, to reduce verbatim repetition. (Default: 0.0)
+    pub fn frequency_penalty(mut self, frequency_penalty: f32) -> Self {
+        self.frequency_penalty = Some(frequency_penalty);
+        self
+    }
+
+    /// Whether the repetition penalties also apply to the newline token. (Default: true)
+    pub fn penalize_newline(mut self, penalize_newline: bool) -> Self {
+        self.penalize_newline = Some(penalize_newline);
+        self
+    }
+
+    /// Number of prompt tokens processed in parallel. Larger batches are faster but use more memory. (Default: 512)
+    pub fn num_batch(mut self, num_batch: u32) -> Self {
+        self.num_batch = Some(num_batch);
+        self
+    }
+
+    /// The GPU used for small tensors, and for all the computation when the model isn't split across GPUs. (Default: 0)
+    pub fn main_gpu(mut self, main_gpu: u32) -> Self {
+        self.main_gpu = Some(main_gpu);
+        self
+    }
+
+    /// Reduces the VRAM usage at the cost of performance. (Default: false)
+    pub fn low_vram(mut self, low_vram: bool) -> Self {
+        self.low_vram = Some(low_vram);
+        self
+    }
+
+    /// Only loads the vocabulary, not the weights. (Default: false)
+    pub fn vocab_only(mut self, vocab_only: bool) -> Self {
+        self.vocab_only = Some(vocab_only);
+        self
+    }
+
+    /// Memory-maps the model file instead of reading it, so it is loaded on demand and can be shared between processes. (Default: true on most systems)
+    pub fn use_mmap(mut self, use_mmap: bool) -> Self {
+        self.use_mmap = Some(use_mmap);
+        self
+    }
+
+    /// Locks the model in memory, so it isn't swapped out. (Default: false)
+    pub fn use_mlock(mut self, use_mlock: bool) -> Self {
+        self.use_mlock = Some(use_mlock);
+        self
+    }
+
+    /// Enables NUMA optimizations on systems with several CPU sockets. (Default: false)
+    pub fn numa(mut self, numa: bool) -> Self {
+        self.numa = Some(numa);
+        self
+    }
+
     /// Add an additional model parameter not listed in the documentation for the Modelfile
     pub fn extra<K, V>(mut self, key: K, value: V) -> Self
     where
@@ -226,3 +476,207 @@ impl ModelOptions {
         self
     }
 }
+
+/// An option whose value the server would reject or misinterpret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionViolation {
+    /// The name of the option, as sent to the server.
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for OptionViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// The options with a typed setter, which must not be set through [`ModelOptions::extra`].
+const TYPED_OPTIONS: &[&str] = &[
+    "mirostat",
+    "mirostat_eta",
+    "mirostat_tau",
+    "num_ctx",
+    "num_gqa",
+    "num_gpu",
+    "num_thread",
+    "repeat_last_n",
+    "repeat_penalty",
+    "temperature",
+    "seed",
+    "stop",
+    "tfs_z",
+    "num_predict",
+    "top_k",
+    "top_p",
+    "min_p",
+    "num_keep",
+    "typical_p",
+    "presence_penalty",
+    "frequency_penalty",
+    "penalize_newline",
+    "num_batch",
+    "main_gpu",
+    "low_vram",
+    "vocab_only",
+    "use_mmap",
+    "use_mlock",
+    "numa",
+];
+
+impl ModelOptions {
+    /// Merges `overrides` over these options, field by field: the options set in `overrides`
+    /// win, the others are kept. [`extra`](ModelOptions::extra) parameters are merged by key.
+    pub fn merge(&self, overrides: &ModelOptions) -> ModelOptions {
+        let mut extra = self.extra.clone();
+        extra.extend(overrides.extra.clone());
+
+        ModelOptions {
+            mirostat: overrides.mirostat.or(self.mirostat),
+            mirostat_eta: overrides.mirostat_eta.or(self.mirostat_eta),
+            mirostat_tau: overrides.mirostat_tau.or(self.mirostat_tau),
+            num_ctx: overrides.num_ctx.or(self.num_ctx),
+            num_gqa: overrides.num_gqa.or(self.num_gqa),
+            num_gpu: overrides.num_gpu.or(self.num_gpu),
+            num_thread: overrides.num_thread.or(self.num_thread),
+            repeat_last_n: overrides.repeat_last_n.or(self.repeat_last_n),
+            repeat_penalty: overrides.repeat_penalty.or(self.repeat_penalty),
+            temperature: overrides.temperature.or(self.temperature),
+            seed: overrides.seed.or(self.seed),
+            stop: overrides.stop.clone().or_else(|| self.stop.clone()),
+            tfs_z: overrides.tfs_z.or(self.tfs_z),
+            num_predict: overrides.num_predict.or(self.num_predict),
+            top_k: overrides.top_k.or(self.top_k),
+            top_p: overrides.top_p.or(self.top_p),
+            min_p: overrides.min_p.or(self.min_p),
+            num_keep: overrides.num_keep.or(self.num_keep),
+            typical_p: overrides.typical_p.or(self.typical_p),
+            presence_penalty: overrides.presence_penalty.or(self.presence_penalty),
+            frequency_penalty: overrides.frequency_penalty.or(self.frequency_penalty),
+            penalize_newline: overrides.penalize_newline.or(self.penalize_newline),
+            num_batch: overrides.num_batch.or(self.num_batch),
+            main_gpu: overrides.main_gpu.or(self.main_gpu),
+            low_vram: overrides.low_vram.or(self.low_vram),
+            vocab_only: overrides.vocab_only.or(self.vocab_only),
+            use_mmap: overrides.use_mmap.or(self.use_mmap),
+            use_mlock: overrides.use_mlock.or(self.use_mlock),
+            numa: overrides.numa.or(self.numa),
+            extra,
+        }
+    }
+
+    /// Checks the options that are set, and returns every invalid one.
+    ///
+    /// Probabilities such as `top_p` must be in `[0, 1]`, `mirostat` must be 0, 1 or 2, sizes
+    /// such as `num_ctx` must be positive, the special negative values of `num_predict`,
+    /// `num_keep` and `repeat_last_n` are accepted, and floating point values must be finite.
+    /// An [`extra`](ModelOptions::extra) parameter that has a typed setter is also reported,
+    /// since it would be sent twice.
+    pub fn validate(&self) -> Result<(), Vec<OptionViolation>> {
+        let mut violations = Vec::new();
+        let mut check = |field: &str, valid: bool, message: &str| {
+            if !valid {
+                violations.push(OptionViolation {
+                    field: field.to_string(),
+                    message: message.to_string(),
+                });
+            }
+        };
+
+        if let Some(mirostat) = self.mirostat {
+            check("mirostat", mirostat <= 2, "must be 0, 1 or 2");
+        }
+
+        for (field, value) in [
+            ("mirostat_eta", self.mirostat_eta),
+            ("mirostat_tau", self.mirostat_tau),
+            ("repeat_penalty", self.repeat_penalty),
+            ("temperature", self.temperature),
+            ("tfs_z", self.tfs_z),
+        ] {
+            if let Some(value) = value {
+                check(
+                    field,
+                    value.is_finite() && value >= 0.0,
+                    "must be a non-negative number",
+                );
+            }
+        }
+        for (field, value) in [
+            ("top_p", self.top_p),
+            ("min_p", self.min_p),
+            ("typical_p", self.typical_p),
+        ] {
+            if let Some(value) = value {
+                check(
+                    field,
+                    (0.0..=1.0).contains(&value),
+                    "must be between 0 and 1",
+                );
+            }
+        }
+        for (field, value) in [
+            ("presence_penalty", self.presence_penalty),
+            ("frequency_penalty", self.frequency_penalty),
+        ] {
+            if let Some(value) = value {
+                check(field, value.is_finite(), "must be a finite number");
+            }
+        }
+
+        if let Some(num_ctx) = self.num_ctx {
+            check("num_ctx", num_ctx > 0, "must be positive");
+        }
+        for (field, value) in [("num_gqa", self.num_gqa), ("num_batch", self.num_batch)] {
+            if let Some(value) = value {
+                check(field, value > 0, "must be positive");
+            }
+        }
+
+        if let Some(num_predict) = self.num_predict {
+            check(
+                "num_predict",
+                num_predict >= -2,
+                "must be positive, -1 for infinite generation or -2 to fill the context",
+            );
+        }
+        if let Some(num_keep) = self.num_keep {
+            check(
+                "num_keep",
+                num_keep >= -1,
+                "must be positive, or -1 to keep all",
+            );
+        }
+        if let Some(repeat_last_n) = self.repeat_last_n {
+            check(
+                "repeat_last_n",
+                repeat_last_n >= -1,
+                "must be positive, 0 to disable or -1 for the context size",
+            );
+        }
+
+        if let Some(stop) = &self.stop {
+            check(
+                "stop",
+                stop.iter().all(|stop| !stop.is_empty()),
+                "must not contain an empty sequence",
+            );
+        }
+
+        let mut extra = self.extra.keys().collect::<Vec<_>>();
+        extra.sort();
+        for key in extra {
+            check(
+                key,
+                !TYPED_OPTIONS.contains(&key.as_str()),
+                "has a typed setter, which must be used instead of `extra`",
+            );
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
@@ -6,12 +6,12 @@ impl Ollama {
     /// Copy a model. Creates a model with another name from an existing model.
     pub async fn copy_model(
         &self,
-        source: String,
-        destination: String,
+        source: impl Into<String>,
+        destination: impl Into<String>,
     ) -> crate::error::Result<()> {
         let request = CopyModelRequest {
-            source,
-            destination,
+            source: source.into(),
+            destination: destination.into(),
         };
 
         let url = format!("{}api/copy", self.url_str());
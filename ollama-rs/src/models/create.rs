@@ -82,7 +82,7 @@ impl Ollama {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum QuantizationType {
     #[serde(rename = "q2_K")]
     Q2K,
@@ -115,7 +115,7 @@ pub enum QuantizationType {
 }
 
 /// A create model request to Ollama.
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateModelRequest {
     /// Name of the model to create
     #[serde(rename = "model")]
@@ -151,9 +151,9 @@ pub struct CreateModelRequest {
 }
 
 impl CreateModelRequest {
-    pub fn new(model_name: String) -> Self {
+    pub fn new(model_name: impl Into<String>) -> Self {
         Self {
-            model_name,
+            model_name: model_name.into(),
             from_model: None,
             files: None,
             adapters: None,
@@ -167,8 +167,8 @@ impl CreateModelRequest {
         }
     }
 
-    pub fn from_model(mut self, from_model: String) -> Self {
-        self.from_model = Some(from_model);
+    pub fn from_model(mut self, from_model: impl Into<String>) -> Self {
+        self.from_model = Some(from_model.into());
         self
     }
 
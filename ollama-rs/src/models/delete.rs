@@ -4,8 +4,10 @@ use crate::{error::OllamaError, Ollama};
 
 impl Ollama {
     /// Delete a model and its data.
-    pub async fn delete_model(&self, model_name: String) -> crate::error::Result<()> {
-        let request = DeleteModelRequest { model_name };
+    pub async fn delete_model(&self, model_name: impl Into<String>) -> crate::error::Result<()> {
+        let request = DeleteModelRequest {
+            model_name: model_name.into(),
+        };
 
         let url = format!("{}api/delete", self.url_str());
         let builder = self.reqwest_client.delete(url);
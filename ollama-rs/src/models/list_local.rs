@@ -30,3 +30,57 @@ impl Ollama {
 struct ListLocalModelsResponse {
     models: Vec<LocalModel>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_models_are_parsed_with_details() {
+        let response = serde_json::from_str::<ListLocalModelsResponse>(
+            r#"{
+                "models": [
+                    {
+                        "name": "llama3.2:latest",
+                        "model": "llama3.2:latest",
+                        "modified_at": "2024-06-04T14:38:31.83753-07:00",
+                        "size": 2019393189,
+                        "digest": "a80c4f17acd55265feec403c7aef86be0c25983ab279d83f3bcd3abbcb5b8b72",
+                        "details": {
+                            "parent_model": "",
+                            "format": "gguf",
+                            "family": "llama",
+                            "families": ["llama"],
+                            "parameter_size": "3.2B",
+                            "quantization_level": "Q4_K_M"
+                        }
+                    },
+                    {
+                        "name": "old-server-model:latest",
+                        "modified_at": "2024-06-04T14:38:31Z",
+                        "size": 1
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let model = &response.models[0];
+        assert_eq!(model.digest.len(), 64);
+        assert_eq!(model.details.family, "llama");
+        assert_eq!(
+            model.details.families.as_deref(),
+            Some(&["llama".to_string()][..])
+        );
+        assert_eq!(model.details.parameter_size, "3.2B");
+        assert_eq!(model.details.quantization_level, "Q4_K_M");
+        assert_eq!(model.modified_at, "2024-06-04T14:38:31.83753-07:00");
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            model.modified_at_datetime().map(|time| time.timestamp()),
+            Some(1717537111)
+        );
+
+        assert_eq!(response.models[1].details, Default::default());
+    }
+}
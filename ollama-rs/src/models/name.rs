@@ -0,0 +1,319 @@
+//! Parsing and comparison of model names.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ModelNameError;
+
+/// The registry of models without a registry in their name.
+pub const DEFAULT_REGISTRY: &str = "registry.ollama.ai";
+/// The namespace of models without a namespace in their name.
+pub const DEFAULT_NAMESPACE: &str = "library";
+/// The tag of models without a tag in their name.
+pub const DEFAULT_TAG: &str = "latest";
+
+/// The name of a model, in the form `[registry/][namespace/]model[:tag][@digest]`.
+///
+/// Missing parts are filled with [`DEFAULT_REGISTRY`], [`DEFAULT_NAMESPACE`] and
+/// [`DEFAULT_TAG`], and names are compared ignoring ASCII case, like the server does. A model
+/// name converts into a `String`, so it can be passed to every model API. The APIs also take
+/// plain strings, which are sent without being validated:
+///
+/// ```
+/// use ollama_rs::models::name::ModelName;
+///
+/// let name: ModelName = "llama3".parse().unwrap();
+/// assert_eq!(name, "registry.ollama.ai/library/llama3:latest".parse().unwrap());
+/// assert_eq!(name.to_string(), "llama3:latest");
+/// assert_eq!(name.tag(), "latest");
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ModelName {
+    registry: String,
+    namespace: String,
+    model: String,
+    tag: String,
+    digest: Option<String>,
+}
+
+impl ModelName {
+    /// Parses a model name, and fills in the default registry, namespace and tag.
+    ///
+    /// Each part must start with an ASCII letter or digit (or `_` for the tag), and only
+    /// contain ASCII letters, digits, `_`, `-` and `.`, up to 80 characters. The registry may
+    /// also contain a port, and the digest must be a SHA-256 digest such as `sha256:<64 hex>`.
+    pub fn parse(name: &str) -> Result<Self, ModelNameError> {
+        let error = |reason: &str| ModelNameError {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let (rest, digest) = match name.split_once('@') {
+            Some((rest, digest)) => (rest, Some(digest)),
+            None => (name, None),
+        };
+        if let Some(digest) = digest {
+            let hex = digest
+                .strip_prefix("sha256:")
+                .or_else(|| digest.strip_prefix("sha256-"))
+                .ok_or_else(|| error("the digest must start with `sha256:`"))?;
+            if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(error("the digest must have 64 hexadecimal digits"));
+            }
+        }
+
+        // A `:` before the last `/` is the port of the registry
+        let (path, tag) = match rest.rfind(':') {
+            Some(index) if !rest[index..].contains('/') => {
+                (&rest[..index], Some(&rest[index + 1..]))
+            }
+            _ => (rest, None),
+        };
+        let (registry, namespace, model) = match path.split('/').collect::<Vec<_>>()[..] {
+            [model] => (None, None, model),
+            [namespace, model] => (None, Some(namespace), model),
+            [registry, namespace, model] => (Some(registry), Some(namespace), model),
+            _ => return Err(error("expected at most `registry/namespace/model`")),
+        };
+
+        let parts = [
+            ("registry", registry),
+            ("namespace", namespace),
+            ("model", Some(model)),
+            ("tag", tag),
+        ];
+        for (kind, part) in parts {
+            if let Some(part) = part {
+                check_part(kind, part).map_err(|reason| error(&reason))?;
+            }
+        }
+
+        Ok(Self {
+            registry: registry.unwrap_or(DEFAULT_REGISTRY).to_string(),
+            namespace: namespace.unwrap_or(DEFAULT_NAMESPACE).to_string(),
+            model: model.to_string(),
+            tag: tag.unwrap_or(DEFAULT_TAG).to_string(),
+            digest: digest.map(str::to_string),
+        })
+    }
+
+    pub fn registry(&self) -> &str {
+        &self.registry
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn digest(&self) -> Option<&str> {
+        self.digest.as_deref()
+    }
+
+    /// Returns this name with another tag.
+    pub fn with_tag(mut self, tag: &str) -> Result<Self, ModelNameError> {
+        check_part("tag", tag).map_err(|reason| ModelNameError {
+            name: self.to_string(),
+            reason,
+        })?;
+        self.tag = tag.to_string();
+        Ok(self)
+    }
+
+    /// Returns this name without its digest.
+    pub fn without_digest(mut self) -> Self {
+        self.digest = None;
+        self
+    }
+
+    /// The name with every part, such as `registry.ollama.ai/library/llama3:latest`.
+    pub fn full_name(&self) -> String {
+        let mut name = format!(
+            "{}/{}/{}:{}",
+            self.registry, self.namespace, self.model, self.tag
+        );
+        if let Some(digest) = &self.digest {
+            name.push('@');
+            name.push_str(digest);
+        }
+        name
+    }
+
+    /// The parts compared by equality, in lowercase.
+    fn normalized(&self) -> [String; 5] {
+        [
+            self.registry.to_ascii_lowercase(),
+            self.namespace.to_ascii_lowercase(),
+            self.model.to_ascii_lowercase(),
+            self.tag.to_ascii_lowercase(),
+            self.digest
+                .as_deref()
+                .unwrap_or_default()
+                .to_ascii_lowercase()
+                .replacen("sha256-", "sha256:", 1),
+        ]
+    }
+}
+
+/// Checks a part of a name, whose `kind` is `registry`, `namespace`, `model` or `tag`.
+fn check_part(kind: &str, part: &str) -> Result<(), String> {
+    let max_len = if kind == "registry" { 350 } else { 80 };
+
+    let Some(first) = part.chars().next() else {
+        return Err(format!("the {kind} is empty"));
+    };
+    if part.len() > max_len {
+        return Err(format!("the {kind} is longer than {max_len} characters"));
+    }
+    if !(first.is_ascii_alphanumeric() || (kind == "tag" && first == '_')) {
+        return Err(format!("the {kind} can't start with `{first}`"));
+    }
+    // The registry may have a port
+    let valid = |c: char| {
+        c.is_ascii_alphanumeric()
+            || matches!(c, '_' | '-' | '.')
+            || (kind == "registry" && c == ':')
+    };
+    if let Some(invalid) = part.chars().find(|c| !valid(*c)) {
+        return Err(format!("the {kind} can't contain `{invalid}`"));
+    }
+    Ok(())
+}
+
+impl fmt::Display for ModelName {
+    /// Writes the shortest equivalent name, such as `llama3:latest`: the default registry and
+    /// namespace are left out, and the tag is always included.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.registry != DEFAULT_REGISTRY {
+            write!(f, "{}/{}/", self.registry, self.namespace)?;
+        } else if self.namespace != DEFAULT_NAMESPACE {
+            write!(f, "{}/", self.namespace)?;
+        }
+        write!(f, "{}:{}", self.model, self.tag)?;
+        if let Some(digest) = &self.digest {
+            write!(f, "@{digest}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for ModelName {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl Eq for ModelName {}
+
+impl Hash for ModelName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state);
+    }
+}
+
+impl FromStr for ModelName {
+    type Err = ModelNameError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::parse(name)
+    }
+}
+
+impl TryFrom<&str> for ModelName {
+    type Error = ModelNameError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        Self::parse(name)
+    }
+}
+
+impl TryFrom<String> for ModelName {
+    type Error = ModelNameError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Self::parse(&name)
+    }
+}
+
+impl From<ModelName> for String {
+    fn from(name: ModelName) -> Self {
+        name.to_string()
+    }
+}
+
+impl From<&ModelName> for String {
+    fn from(name: &ModelName) -> Self {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(name: &str) -> ModelName {
+        ModelName::parse(name).unwrap()
+    }
+
+    #[test]
+    fn defaults_are_filled_in() {
+        let digest = format!("sha256:{}", "a".repeat(64));
+        let parsed = name(&format!("localhost:5000/team/coder:7b-q4@{digest}"));
+        assert_eq!(parsed.registry(), "localhost:5000");
+        assert_eq!(parsed.namespace(), "team");
+        assert_eq!(parsed.model(), "coder");
+        assert_eq!(parsed.tag(), "7b-q4");
+        assert_eq!(parsed.digest(), Some(digest.as_str()));
+
+        assert_eq!(
+            name("llama3").full_name(),
+            "registry.ollama.ai/library/llama3:latest"
+        );
+        assert_eq!(name("me/llama3").to_string(), "me/llama3:latest");
+        assert_eq!(name("llama3.2:1b").to_string(), "llama3.2:1b");
+    }
+
+    #[test]
+    fn equivalent_names_are_equal() {
+        assert_eq!(name("llama3"), name("llama3:latest"));
+        assert_eq!(name("llama3"), name("library/llama3"));
+        assert_eq!(
+            name("Llama3"),
+            name("registry.ollama.ai/library/llama3:LATEST")
+        );
+        assert_ne!(name("llama3"), name("llama3:8b"));
+        assert_ne!(name("llama3"), name("me/llama3"));
+
+        let mut names = std::collections::HashSet::new();
+        names.insert(name("llama3"));
+        assert!(names.contains(&name("LLAMA3:latest")));
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        for invalid in [
+            "",
+            "llama3:",
+            "-llama3",
+            "llama 3",
+            "a/b/c/d",
+            "llama3@md5:abc",
+            "llama3@sha256:abc",
+        ] {
+            assert!(ModelName::parse(invalid).is_err(), "{invalid:?} is invalid");
+        }
+    }
+}
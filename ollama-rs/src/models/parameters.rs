@@ -0,0 +1,201 @@
+//! Parsing of the parameters of a model, as listed by `/api/show` or in a Modelfile.
+
+use serde_json::{Map, Value};
+
+use super::{ModelInfo, ModelOptions};
+
+impl ModelOptions {
+    /// Parses parameters in the format of [`ModelInfo::parameters`]: one `key value` pair per
+    /// line, with quoted string values, such as:
+    ///
+    /// ```text
+    /// num_ctx                        4096
+    /// stop                           "<|start_header_id|>"
+    /// stop                           "<|eot_id|>"
+    /// ```
+    ///
+    /// The `stop` values are collected, and read as strings even if they aren't quoted. For
+    /// other repeated keys, the last value wins, like on the server. Parameters without a typed
+    /// setter are kept as [`extra`](ModelOptions::extra) parameters.
+    pub fn from_parameters(parameters: &str) -> crate::error::Result<ModelOptions> {
+        let mut options = Map::new();
+        let mut stop = Vec::new();
+        for line in parameters.lines() {
+            let Some((key, value)) = line.trim().split_once(char::is_whitespace) else {
+                continue;
+            };
+
+            if key == "stop" {
+                stop.push(Value::String(parse_string(value.trim())));
+            } else {
+                options.insert(key.to_string(), parse_value(value.trim()));
+            }
+        }
+        if !stop.is_empty() {
+            options.insert("stop".to_string(), Value::Array(stop));
+        }
+
+        Ok(serde_json::from_value(Value::Object(options))?)
+    }
+
+    /// Parses the `PARAMETER` instructions of a Modelfile, see
+    /// [`ModelOptions::from_parameters`].
+    pub fn from_modelfile(modelfile: &str) -> crate::error::Result<ModelOptions> {
+        let mut parameters = String::new();
+        // Whether the line is inside a `"""` string, such as a template
+        let mut in_multiline_string = false;
+
+        for line in modelfile.lines() {
+            let starts_in_string = in_multiline_string;
+            if line.matches("\"\"\"").count() % 2 == 1 {
+                in_multiline_string = !in_multiline_string;
+            }
+            if starts_in_string {
+                continue;
+            }
+
+            let line = line.trim();
+            let Some((instruction, parameter)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            if instruction.eq_ignore_ascii_case("PARAMETER") {
+                parameters.push_str(parameter.trim());
+                parameters.push('\n');
+            }
+        }
+
+        Self::from_parameters(&parameters)
+    }
+}
+
+impl ModelInfo {
+    /// The parameters of the model, parsed with [`ModelOptions::from_parameters`]. These are
+    /// the defaults of the model, which the options of a request override.
+    pub fn options(&self) -> crate::error::Result<ModelOptions> {
+        ModelOptions::from_parameters(&self.parameters)
+    }
+}
+
+/// A quoted or unquoted string value.
+fn parse_string(value: &str) -> String {
+    if value.starts_with('"') {
+        serde_json::from_str::<String>(value)
+            .unwrap_or_else(|_| value.trim_matches('"').to_string())
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_value(value: &str) -> Value {
+    if value.starts_with('"') {
+        return Value::String(parse_string(value));
+    }
+
+    if let Ok(value) = value.parse::<bool>() {
+        Value::Bool(value)
+    } else if let Ok(value) = value.parse::<i64>() {
+        Value::from(value)
+    } else if let Some(value) = value
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        Value::Number(value)
+    } else {
+        Value::String(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameters_are_parsed_into_options() {
+        let parameters = "num_ctx                        4096\n\
+                          stop                           \"<|start_header_id|>\"\n\
+                          stop                           \"<|eot_id|>\"\n\
+                          temperature                    0.5\n\
+                          penalize_newline               false\n\
+                          custom                         \"value\"\n";
+
+        let options = ModelOptions::from_parameters(parameters).unwrap();
+        assert_eq!(
+            options,
+            ModelOptions::default()
+                .num_ctx(4096)
+                .stop(vec![
+                    "<|start_header_id|>".to_string(),
+                    "<|eot_id|>".to_string()
+                ])
+                .temperature(0.5)
+                .penalize_newline(false)
+                .extra("custom", "value")
+        );
+    }
+
+    #[test]
+    fn unquoted_stops_and_repeated_keys_are_parsed() {
+        let parameters = "stop 42\n\
+                          stop true\n\
+                          stop \"<|eot_id|>\"\n\
+                          temperature 0.5\n\
+                          temperature 0.7\n";
+
+        let options = ModelOptions::from_parameters(parameters).unwrap();
+        assert_eq!(
+            options,
+            ModelOptions::default()
+                .stop(vec![
+                    "42".to_string(),
+                    "true".to_string(),
+                    "<|eot_id|>".to_string()
+                ])
+                .temperature(0.7)
+        );
+    }
+
+    #[test]
+    fn modelfile_parameters_are_parsed() {
+        let modelfile = "FROM llama3.2\n\
+                         TEMPLATE \"\"\"{{ .System }}\n\
+                         PARAMETER num_ctx 1\n\
+                         {{ .Prompt }}\"\"\"\n\
+                         PARAMETER stop \"User:\"\n\
+                         parameter top_k 20\n";
+
+        let options = ModelOptions::from_modelfile(modelfile).unwrap();
+        assert_eq!(
+            options,
+            ModelOptions::default()
+                .stop(vec!["User:".to_string()])
+                .top_k(20)
+        );
+    }
+
+    #[test]
+    fn model_info_accessors() {
+        let info = serde_json::from_value::<ModelInfo>(serde_json::json!({
+            "modelfile": "FROM llama3.2\n",
+            "parameters": "num_ctx 8192",
+            "model_info": {
+                "general.architecture": "llama",
+                "general.parameter_count": 3212749888u64,
+                "general.file_type": 15,
+                "llama.context_length": 131072,
+                "llama.embedding_length": 3072,
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(info.architecture(), Some("llama"));
+        assert_eq!(info.parameter_count(), Some(3212749888));
+        assert_eq!(info.context_length(), Some(131072));
+        assert_eq!(info.embedding_length(), Some(3072));
+        assert_eq!(info.quantization(), Some("Q4_K_M"));
+        assert_eq!(
+            info.options().unwrap(),
+            ModelOptions::default().num_ctx(8192)
+        );
+    }
+}
@@ -17,14 +17,14 @@ impl Ollama {
     /// - `allow_insecure` - Allow insecure connections to the library. Only use this if you are pulling from your own library during development.
     pub async fn pull_model_stream(
         &self,
-        model_name: String,
+        model_name: impl Into<String>,
         allow_insecure: bool,
     ) -> crate::error::Result<PullModelStatusStream> {
         use crate::error::{InternalOllamaError, OllamaError};
         use tokio_stream::StreamExt;
 
         let request = PullModelRequest {
-            model_name,
+            model_name: model_name.into(),
             allow_insecure,
             stream: true,
         };
@@ -89,11 +89,11 @@ impl Ollama {
     /// - `allow_insecure` - Allow insecure connections to the library. Only use this if you are pulling from your own library during development.
     pub async fn pull_model(
         &self,
-        model_name: String,
+        model_name: impl Into<String>,
         allow_insecure: bool,
     ) -> crate::error::Result<PullModelStatus> {
         let request = PullModelRequest {
-            model_name,
+            model_name: model_name.into(),
             allow_insecure,
             stream: false,
         };
@@ -18,14 +18,14 @@ impl Ollama {
     /// - `allow_insecure` - Allow insecure connections to the library. Only use this if you are pushing to your library during development.
     pub async fn push_model_stream(
         &self,
-        model_name: String,
+        model_name: impl Into<String>,
         allow_insecure: bool,
     ) -> crate::error::Result<PushModelStatusStream> {
         use crate::error::OllamaError;
         use tokio_stream::StreamExt;
 
         let request = PushModelRequest {
-            model_name,
+            model_name: model_name.into(),
             allow_insecure,
             stream: true,
         };
@@ -69,11 +69,11 @@ impl Ollama {
     /// - `allow_insecure` - Allow insecure connections to the library. Only use this if you are pushing to your library during development.
     pub async fn push_model(
         &self,
-        model_name: String,
+        model_name: impl Into<String>,
         allow_insecure: bool,
     ) -> crate::error::Result<PushModelStatus> {
         let request = PushModelRequest {
-            model_name,
+            model_name: model_name.into(),
             allow_insecure,
             stream: false,
         };
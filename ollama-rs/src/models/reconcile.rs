@@ -0,0 +1,572 @@
+//! Reconciliation of the local models with a desired state.
+
+use crate::{error::OllamaError, Ollama};
+
+use super::{create::CreateModelRequest, name::ModelName, LocalModel, ModelInfo};
+
+/// The models that should be present, or absent, on the server.
+///
+/// ```ignore
+/// let manifest = ModelManifest::new()
+///     .pull("llama3.2")
+///     .pull("nomic-embed-text")
+///     .create(
+///         CreateModelRequest::new("assistant")
+///             .from_model("llama3.2".to_string())
+///             .system("You are a helpful assistant".to_string()),
+///     )
+///     .delete("llama2");
+///
+/// let plan = ollama.plan_models(&manifest).await?;
+/// for action in &plan.actions {
+///     println!("{action}");
+/// }
+///
+/// ollama
+///     .reconcile_models(&manifest, |progress| {
+///         println!("[{}/{}] {}: {}", progress.step + 1, progress.steps, progress.action, progress.status);
+///     })
+///     .await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ModelManifest {
+    pub pull: Vec<String>,
+    pub create: Vec<CreateModelRequest>,
+    pub delete: Vec<String>,
+    /// Whether the models to pull are pulled again when they are present, to update them.
+    pub refresh: bool,
+    /// Whether pulls may use insecure connections to the registry.
+    pub allow_insecure: bool,
+    /// Whether [`Ollama::reconcile_models`] only plans the actions, without running them.
+    pub dry_run: bool,
+}
+
+impl ModelManifest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a model to pull if it is missing. A name with a digest, such as
+    /// `llama3.2@sha256:<digest>`, is also pulled when the local model has another digest.
+    ///
+    /// A pinned model is pulled by its digest, not by its tag, so the local model is never
+    /// replaced with another digest than the pinned one.
+    pub fn pull(mut self, model: impl Into<String>) -> Self {
+        self.pull.push(model.into());
+        self
+    }
+
+    /// Adds a model to create if it is missing, or to create again if it differs from the
+    /// request, see [`ReconcileReason::Changed`].
+    pub fn create(mut self, request: CreateModelRequest) -> Self {
+        self.create.push(request);
+        self
+    }
+
+    /// Adds a model to delete if it is present.
+    pub fn delete(mut self, model: impl Into<String>) -> Self {
+        self.delete.push(model.into());
+        self
+    }
+
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    pub fn allow_insecure(mut self, allow_insecure: bool) -> Self {
+        self.allow_insecure = allow_insecure;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+/// Why an action is needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconcileReason {
+    /// The model isn't present.
+    Missing,
+    /// The digest of the local model differs from the digest of the manifest.
+    DigestMismatch { local: String },
+    /// The local model differs from the create request in these fields.
+    ///
+    /// The fields compared are `from` (when the server reports the parent model), `template`,
+    /// `system`, `license` and `parameters`. Files, adapters, messages and quantization can't be
+    /// compared, so changing them alone doesn't create the model again.
+    Changed { fields: Vec<&'static str> },
+    /// The model is present, and pulled again because of [`ModelManifest::refresh`].
+    Refresh,
+    /// The model is listed to be deleted.
+    Unwanted,
+}
+
+/// An action that brings the local models closer to a [`ModelManifest`].
+#[derive(Debug, Clone)]
+pub enum ReconcileAction {
+    Pull {
+        /// The model to pull, with its digest if the manifest pins one.
+        model: String,
+        reason: ReconcileReason,
+    },
+    Create {
+        request: Box<CreateModelRequest>,
+        reason: ReconcileReason,
+    },
+    Delete {
+        model: String,
+    },
+}
+
+impl ReconcileAction {
+    /// The name of the model the action applies to.
+    pub fn model(&self) -> &str {
+        match self {
+            ReconcileAction::Pull { model, .. } | ReconcileAction::Delete { model } => model,
+            ReconcileAction::Create { request, .. } => &request.model_name,
+        }
+    }
+
+    pub fn reason(&self) -> &ReconcileReason {
+        match self {
+            ReconcileAction::Pull { reason, .. } | ReconcileAction::Create { reason, .. } => reason,
+            ReconcileAction::Delete { .. } => &ReconcileReason::Unwanted,
+        }
+    }
+}
+
+impl std::fmt::Display for ReconcileAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = match self {
+            ReconcileAction::Pull { .. } => "pull",
+            ReconcileAction::Create { .. } => "create",
+            ReconcileAction::Delete { .. } => "delete",
+        };
+        write!(f, "{verb} {}", self.model())?;
+
+        match self.reason() {
+            ReconcileReason::Missing => write!(f, " (missing)"),
+            ReconcileReason::DigestMismatch { local } => write!(f, " (local digest {local})"),
+            ReconcileReason::Changed { fields } => write!(f, " ({} changed)", fields.join(", ")),
+            ReconcileReason::Refresh => write!(f, " (refresh)"),
+            ReconcileReason::Unwanted => Ok(()),
+        }
+    }
+}
+
+/// The actions needed to reconcile the local models with a [`ModelManifest`], in the order
+/// they run: pulls, then creates, which may be based on pulled models, then deletes.
+#[derive(Debug, Clone, Default)]
+pub struct ReconcilePlan {
+    pub actions: Vec<ReconcileAction>,
+}
+
+impl ReconcilePlan {
+    /// Whether the local models already match the manifest.
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// The progress of [`Ollama::reconcile_models`].
+#[derive(Debug, Clone)]
+pub struct ReconcileProgress<'a> {
+    /// The index of the running action.
+    pub step: usize,
+    /// The number of actions.
+    pub steps: usize,
+    pub action: &'a ReconcileAction,
+    /// The status reported by the server, such as `pulling manifest` or `success`.
+    pub status: String,
+    /// The number of bytes transferred so far, when the server reports it.
+    pub completed: Option<u64>,
+    /// The number of bytes to transfer, when the server reports it.
+    pub total: Option<u64>,
+}
+
+impl Ollama {
+    /// Compares the local models with `manifest`, and returns the actions needed to reconcile
+    /// them, without running them.
+    pub async fn plan_models(
+        &self,
+        manifest: &ModelManifest,
+    ) -> crate::error::Result<ReconcilePlan> {
+        let local = self.list_local_models().await?;
+        let mut actions = Vec::new();
+
+        for model in &manifest.pull {
+            let name = ModelName::parse(model)?;
+            let reason = match find_local(&local, &name) {
+                None => Some(ReconcileReason::Missing),
+                Some(local) => match name.digest() {
+                    Some(digest) if normalize_digest(digest) != normalize_digest(&local.digest) => {
+                        Some(ReconcileReason::DigestMismatch {
+                            local: local.digest.clone(),
+                        })
+                    }
+                    _ if manifest.refresh => Some(ReconcileReason::Refresh),
+                    _ => None,
+                },
+            };
+
+            if let Some(reason) = reason {
+                actions.push(ReconcileAction::Pull {
+                    model: name.to_string(),
+                    reason,
+                });
+            }
+        }
+
+        for request in &manifest.create {
+            let name = ModelName::parse(&request.model_name)?;
+            let reason = if find_local(&local, &name).is_none() {
+                Some(ReconcileReason::Missing)
+            } else {
+                let info = self.show_model_info(request.model_name.clone()).await?;
+                let fields = changed_fields(request, &info)?;
+                (!fields.is_empty()).then_some(ReconcileReason::Changed { fields })
+            };
+
+            if let Some(reason) = reason {
+                actions.push(ReconcileAction::Create {
+                    request: Box::new(request.clone()),
+                    reason,
+                });
+            }
+        }
+
+        for model in &manifest.delete {
+            let name = ModelName::parse(model)?;
+            let wanted = manifest.pull.iter().map(String::as_str).chain(
+                manifest
+                    .create
+                    .iter()
+                    .map(|request| request.model_name.as_str()),
+            );
+            for wanted in wanted {
+                if ModelName::parse(wanted).is_ok_and(|wanted| wanted.without_digest() == name) {
+                    return Err(OllamaError::Other(format!(
+                        "Model `{model}` is both wanted and deleted by the manifest"
+                    )));
+                }
+            }
+
+            if let Some(local) = find_local(&local, &name) {
+                actions.push(ReconcileAction::Delete {
+                    model: local.name.clone(),
+                });
+            }
+        }
+
+        Ok(ReconcilePlan { actions })
+    }
+
+    /// Reconciles the local models with `manifest`: plans the actions with
+    /// [`Ollama::plan_models`], then runs them in order, and returns the plan.
+    ///
+    /// `progress` is called when each action starts and with each status reported by the
+    /// server. With the `stream` feature, pulls report the bytes transferred. With
+    /// [`ModelManifest::dry_run`], the actions are only planned. Reconciliation stops at the
+    /// first failing action.
+    pub async fn reconcile_models(
+        &self,
+        manifest: &ModelManifest,
+        mut progress: impl FnMut(ReconcileProgress<'_>),
+    ) -> crate::error::Result<ReconcilePlan> {
+        let plan = self.plan_models(manifest).await?;
+        if manifest.dry_run {
+            return Ok(plan);
+        }
+
+        let steps = plan.actions.len();
+        for (step, action) in plan.actions.iter().enumerate() {
+            let mut report = |status: String, completed: Option<u64>, total: Option<u64>| {
+                progress(ReconcileProgress {
+                    step,
+                    steps,
+                    action,
+                    status,
+                    completed,
+                    total,
+                })
+            };
+            report("starting".to_string(), None, None);
+
+            match action {
+                ReconcileAction::Pull { model, .. } => {
+                    #[cfg(feature = "stream")]
+                    {
+                        use tokio_stream::StreamExt;
+
+                        let mut statuses = self
+                            .pull_model_stream(model.clone(), manifest.allow_insecure)
+                            .await?;
+                        while let Some(status) = statuses.next().await {
+                            let status = status?;
+                            report(status.message, status.completed, status.total);
+                        }
+                    }
+                    #[cfg(not(feature = "stream"))]
+                    {
+                        let status = self
+                            .pull_model(model.clone(), manifest.allow_insecure)
+                            .await?;
+                        report(status.message, status.completed, status.total);
+                    }
+                }
+                ReconcileAction::Create { request, .. } => {
+                    let status = self.create_model((**request).clone()).await?;
+                    report(status.message, None, None);
+                }
+                ReconcileAction::Delete { model } => {
+                    self.delete_model(model.clone()).await?;
+                    report("success".to_string(), None, None);
+                }
+            }
+        }
+
+        Ok(plan)
+    }
+}
+
+fn find_local<'a>(local: &'a [LocalModel], name: &ModelName) -> Option<&'a LocalModel> {
+    let name = name.clone().without_digest();
+    local
+        .iter()
+        .find(|local| ModelName::parse(&local.name).is_ok_and(|local| local == name))
+}
+
+fn normalize_digest(digest: &str) -> String {
+    let digest = digest.to_ascii_lowercase();
+    let hex = digest
+        .strip_prefix("sha256:")
+        .or_else(|| digest.strip_prefix("sha256-"))
+        .unwrap_or(&digest);
+    hex.to_string()
+}
+
+/// The fields of `request` that differ from the local model.
+fn changed_fields(
+    request: &CreateModelRequest,
+    info: &ModelInfo,
+) -> crate::error::Result<Vec<&'static str>> {
+    let mut fields = Vec::new();
+
+    if let Some(from) = &request.from_model {
+        let parent = &info.details.parent_model;
+        let same_parent = match (ModelName::parse(from), ModelName::parse(parent)) {
+            (Ok(from), Ok(parent)) => from == parent,
+            _ => from == parent,
+        };
+        if !parent.is_empty() && !same_parent {
+            fields.push("from");
+        }
+    }
+    if let Some(template) = &request.template {
+        if template.trim() != info.template.trim() {
+            fields.push("template");
+        }
+    }
+    if let Some(system) = &request.system {
+        if system.trim() != info.system.trim() {
+            fields.push("system");
+        }
+    }
+    if let Some(licenses) = &request.license {
+        if !licenses
+            .iter()
+            .all(|license| info.license.contains(license.trim()))
+        {
+            fields.push("license");
+        }
+    }
+    if let Some(parameters) = &request.parameters {
+        let current = info.options()?;
+        if current.merge(parameters) != current {
+            fields.push("parameters");
+        }
+    }
+
+    Ok(fields)
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use serde_json::{json, Value};
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    use super::*;
+    use crate::{
+        models::ModelOptions,
+        test_util::{self, Request, Response},
+    };
+
+    /// Answers each request with the response of its path, and the model of its body for
+    /// `/api/show`.
+    async fn serve(show: Value) -> (Ollama, UnboundedReceiver<Request>) {
+        test_util::serve(move |request| {
+            Response::json(match request.path.as_str() {
+                "/api/tags" => json!({
+                    "models": [
+                        {
+                            "name": "llama3.2:latest",
+                            "modified_at": "2025-01-01T00:00:00Z",
+                            "size": 1,
+                            "digest": "a".repeat(64),
+                        },
+                        {
+                            "name": "assistant:latest",
+                            "modified_at": "2025-01-01T00:00:00Z",
+                            "size": 1,
+                            "digest": "b".repeat(64),
+                        },
+                        {
+                            "name": "llama2:latest",
+                            "modified_at": "2025-01-01T00:00:00Z",
+                            "size": 1,
+                            "digest": "c".repeat(64),
+                        },
+                    ],
+                }),
+                "/api/show" => show[request.body["name"].as_str().unwrap_or_default()].clone(),
+                _ => json!({ "status": "success" }),
+            })
+        })
+        .await
+    }
+
+    /// The next pull, create or delete received by the server, as `<path> <model>`.
+    async fn next_change(requests: &mut UnboundedReceiver<Request>) -> String {
+        loop {
+            let request = requests.recv().await.expect("no request left");
+            if request.path != "/api/tags" && request.path != "/api/show" {
+                let model = request.body["name"]
+                    .as_str()
+                    .or(request.body["model"].as_str())
+                    .unwrap_or_default();
+                return format!("{} {model}", request.path);
+            }
+        }
+    }
+
+    fn manifest() -> ModelManifest {
+        ModelManifest::new()
+            .pull("llama3.2")
+            .pull("nomic-embed-text")
+            .create(
+                CreateModelRequest::new("assistant")
+                    .system("You are terse".to_string())
+                    .parameters(ModelOptions::default().temperature(0.5)),
+            )
+            .delete("llama2")
+            .delete("mistral")
+    }
+
+    #[tokio::test]
+    async fn only_the_differences_are_planned() {
+        let (ollama, _) = serve(json!({
+            "assistant": {
+                "modelfile": "FROM llama3.2\n",
+                "system": "You are verbose",
+                "parameters": "temperature 0.5",
+            },
+        }))
+        .await;
+
+        let plan = ollama.plan_models(&manifest()).await.unwrap();
+        let actions = plan
+            .actions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actions,
+            [
+                "pull nomic-embed-text:latest (missing)",
+                "create assistant (system changed)",
+                "delete llama2:latest",
+            ]
+        );
+
+        let digest = format!("llama3.2@sha256:{}", "d".repeat(64));
+        let plan = ollama
+            .plan_models(&ModelManifest::new().pull(digest))
+            .await
+            .unwrap();
+        assert!(matches!(
+            plan.actions[0].reason(),
+            ReconcileReason::DigestMismatch { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn actions_run_in_order_with_progress() {
+        let (ollama, mut requests) = serve(json!({
+            "assistant": {
+                "modelfile": "FROM llama3.2\n",
+                "system": "You are terse",
+                "parameters": "temperature 0.5",
+            },
+        }))
+        .await;
+
+        let dry_run = ollama
+            .reconcile_models(&manifest().dry_run(true), |_| panic!("nothing runs"))
+            .await
+            .unwrap();
+        assert_eq!(dry_run.actions.len(), 2);
+
+        let mut statuses = Vec::new();
+        let plan = ollama
+            .reconcile_models(&manifest(), |progress| {
+                statuses.push((progress.step, progress.steps, progress.status))
+            })
+            .await
+            .unwrap();
+        assert_eq!(plan.actions.len(), 2);
+        assert_eq!(
+            statuses,
+            [
+                (0, 2, "starting".to_string()),
+                (0, 2, "success".to_string()),
+                (1, 2, "starting".to_string()),
+                (1, 2, "success".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            next_change(&mut requests).await,
+            "/api/pull nomic-embed-text:latest"
+        );
+        assert_eq!(
+            next_change(&mut requests).await,
+            "/api/delete llama2:latest"
+        );
+    }
+
+    #[tokio::test]
+    async fn pinned_digests_are_pulled_by_digest() {
+        let (ollama, mut requests) = serve(json!({})).await;
+
+        let pinned = format!("llama3.2@sha256:{}", "d".repeat(64));
+        let plan = ollama
+            .reconcile_models(&ModelManifest::new().pull(pinned), |_| {})
+            .await
+            .unwrap();
+        assert_eq!(
+            plan.actions[0].to_string(),
+            format!(
+                "pull llama3.2:latest@sha256:{} (local digest {})",
+                "d".repeat(64),
+                "a".repeat(64)
+            )
+        );
+        assert_eq!(
+            next_change(&mut requests).await,
+            format!("/api/pull llama3.2:latest@sha256:{}", "d".repeat(64))
+        );
+    }
+}
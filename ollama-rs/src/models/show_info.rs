@@ -6,17 +6,41 @@ use super::ModelInfo;
 
 impl Ollama {
     /// Show details about a model including modelfile, template, parameters, license, and system prompt.
-    pub async fn show_model_info(&self, model_name: String) -> crate::error::Result<ModelInfo> {
+    pub async fn show_model_info(
+        &self,
+        model_name: impl Into<String>,
+    ) -> crate::error::Result<ModelInfo> {
+        self.show_model_info_request(ModelInfoRequest {
+            model_name: model_name.into(),
+            verbose: None,
+        })
+        .await
+    }
+
+    /// Show details about a model, including the full metadata of `model_info`, such as the
+    /// vocabulary, and the list of tensors of the model.
+    pub async fn show_model_info_verbose(
+        &self,
+        model_name: impl Into<String>,
+    ) -> crate::error::Result<ModelInfo> {
+        self.show_model_info_request(ModelInfoRequest {
+            model_name: model_name.into(),
+            verbose: Some(true),
+        })
+        .await
+    }
+
+    async fn show_model_info_request(
+        &self,
+        request: ModelInfoRequest,
+    ) -> crate::error::Result<ModelInfo> {
         let url = format!("{}api/show", self.url_str());
         let builder = self.reqwest_client.post(url);
 
         #[cfg(feature = "headers")]
         let builder = builder.headers(self.request_headers.clone());
 
-        let res = builder
-            .json(&ModelInfoRequest { model_name })
-            .send()
-            .await?;
+        let res = builder.json(&request).send().await?;
 
         if !res.status().is_success() {
             return Err(OllamaError::Other(res.text().await?));
@@ -34,4 +58,54 @@ impl Ollama {
 struct ModelInfoRequest {
     #[serde(rename = "name")]
     model_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verbose: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbose_model_info_is_parsed() {
+        let info = serde_json::from_str::<ModelInfo>(
+            r#"{
+                "modelfile": "FROM llava\n",
+                "parameters": "",
+                "template": "",
+                "details": {
+                    "parent_model": "",
+                    "format": "gguf",
+                    "family": "llama",
+                    "families": ["llama", "clip"],
+                    "parameter_size": "7B",
+                    "quantization_level": "Q4_0"
+                },
+                "model_info": { "general.architecture": "llama" },
+                "projector_info": { "clip.vision.image_size": 336 },
+                "tensors": [
+                    { "name": "token_embd.weight", "type": "Q4_0", "shape": [4096, 32000] }
+                ],
+                "capabilities": ["completion", "vision"],
+                "modified_at": "2025-01-01T00:00:00Z"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(info.details.families.clone().unwrap(), ["llama", "clip"]);
+        assert_eq!(info.projector_info["clip.vision.image_size"], 336);
+        assert_eq!(info.tensors[0].tensor_type, "Q4_0");
+        assert_eq!(info.tensors[0].shape, [4096, 32000]);
+        assert_eq!(info.modified_at.as_deref(), Some("2025-01-01T00:00:00Z"));
+        #[cfg(feature = "chrono")]
+        assert!(info.modified_at_datetime().is_some());
+        assert_eq!(
+            serde_json::to_value(ModelInfoRequest {
+                model_name: "llava".to_string(),
+                verbose: Some(true),
+            })
+            .unwrap(),
+            serde_json::json!({ "name": "llava", "verbose": true })
+        );
+    }
 }
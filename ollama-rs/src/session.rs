@@ -0,0 +1,570 @@
+//! Many concurrent chat sessions, for server applications.
+
+use std::{
+    collections::{hash_map, HashMap},
+    future::{ready, Future},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use crate::{
+    error::HistoryError,
+    generation::chat::{request::ChatMessageRequest, ChatMessage, ChatMessageResponse},
+    history::new_conversation_id,
+    models::ModelOptions,
+    Ollama,
+};
+
+/// The state of a chat session: its model, options, system prompt and history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<ModelOptions>,
+    /// Sent before the history with each request, without being stored in it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default)]
+    pub history: Vec<ChatMessage>,
+}
+
+impl Session {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            options: None,
+            system_prompt: None,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn options(mut self, options: ModelOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    /// The request for the next turn, with `messages` appended to the history.
+    fn request(&self, messages: &[ChatMessage]) -> ChatMessageRequest {
+        let mut all_messages = Vec::with_capacity(self.history.len() + messages.len() + 1);
+        if let Some(system_prompt) = &self.system_prompt {
+            all_messages.push(ChatMessage::system(system_prompt.clone()));
+        }
+        all_messages.extend_from_slice(&self.history);
+        all_messages.extend_from_slice(messages);
+
+        let mut request = ChatMessageRequest::new(self.model.clone(), all_messages);
+        request.options = self.options.clone();
+        request
+    }
+}
+
+/// Where sessions are persisted, so that they survive eviction and restarts.
+pub trait SessionStorage: Send + Sync + 'static {
+    /// Loads the session with the given ID, if it was saved.
+    fn load(&self, id: &str) -> impl Future<Output = Result<Option<Session>, HistoryError>> + Send;
+    /// Saves the session with the given ID, after it is created and after each turn.
+    fn save(
+        &self,
+        id: &str,
+        session: &Session,
+    ) -> impl Future<Output = Result<(), HistoryError>> + Send;
+    /// Deletes the session with the given ID.
+    fn delete(&self, id: &str) -> impl Future<Output = Result<(), HistoryError>> + Send;
+}
+
+/// Sessions are only kept in memory, and are lost once evicted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoStorage;
+
+impl SessionStorage for NoStorage {
+    fn load(
+        &self,
+        _id: &str,
+    ) -> impl Future<Output = Result<Option<Session>, HistoryError>> + Send {
+        ready(Ok(None))
+    }
+
+    fn save(
+        &self,
+        _id: &str,
+        _session: &Session,
+    ) -> impl Future<Output = Result<(), HistoryError>> + Send {
+        ready(Ok(()))
+    }
+
+    fn delete(&self, _id: &str) -> impl Future<Output = Result<(), HistoryError>> + Send {
+        ready(Ok(()))
+    }
+}
+
+struct Entry {
+    handle: Handle,
+    last_used: Instant,
+}
+
+/// A session in memory, shared by its turns.
+#[derive(Clone)]
+struct Handle {
+    session: Arc<AsyncMutex<Session>>,
+    /// Set when the session is removed, so that a turn in progress doesn't save it again
+    removed: Arc<AtomicBool>,
+}
+
+impl Handle {
+    fn new(session: Session) -> Self {
+        Self {
+            session: Arc::new(AsyncMutex::new(session)),
+            removed: Arc::default(),
+        }
+    }
+
+    fn is_removed(&self) -> bool {
+        self.removed.load(Ordering::SeqCst)
+    }
+}
+
+struct Inner<S> {
+    ollama: Ollama,
+    storage: S,
+    ttl: Option<Duration>,
+    sessions: Mutex<HashMap<String, Entry>>,
+}
+
+/// Holds many concurrent chat sessions, each with its own history, model, options and system
+/// prompt.
+///
+/// Turns of a session are serialized: a second request to a session waits until the first one
+/// is done, so turns never interleave. Sessions that aren't used for the TTL are evicted from
+/// memory; with a [`SessionStorage`] other than [`NoStorage`], they are loaded back on their next
+/// use.
+///
+/// The store can be cloned; clones share the same sessions.
+///
+/// ```ignore
+/// let store = SessionStore::builder(Ollama::default())
+///     .ttl(Duration::from_secs(30 * 60))
+///     .build();
+/// let id = store
+///     .create(Session::new("llama3.2".to_string()).system_prompt("You are a helpful assistant."))
+///     .await?;
+/// let response = store.chat(&id, ChatMessage::user("Hello!".to_string())).await?;
+/// ```
+pub struct SessionStore<S: SessionStorage = NoStorage> {
+    inner: Arc<Inner<S>>,
+}
+
+impl<S: SessionStorage> Clone for SessionStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl SessionStore {
+    /// Creates a store keeping its sessions in memory, forever.
+    pub fn new(ollama: Ollama) -> Self {
+        Self::builder(ollama).build()
+    }
+
+    /// Returns a [`SessionStoreBuilder`] to configure the storage and the TTL of a store.
+    pub fn builder(ollama: Ollama) -> SessionStoreBuilder {
+        SessionStoreBuilder {
+            ollama,
+            storage: NoStorage,
+            ttl: None,
+        }
+    }
+}
+
+impl<S: SessionStorage> SessionStore<S> {
+    /// Creates a store persisting its sessions in `storage`, and keeping them in memory forever.
+    pub fn with_storage(ollama: Ollama, storage: S) -> Self {
+        SessionStore::builder(ollama).storage(storage).build()
+    }
+
+    /// Creates a session with a new ID, and returns the ID.
+    pub async fn create(&self, session: Session) -> crate::error::Result<String> {
+        let id = new_conversation_id();
+        self.create_with_id(id.clone(), session).await?;
+        Ok(id)
+    }
+
+    /// Creates a session with the given ID.
+    pub async fn create_with_id(
+        &self,
+        id: impl Into<String>,
+        session: Session,
+    ) -> crate::error::Result<()> {
+        let id = id.into();
+        self.evict_expired();
+        if self.inner.storage.load(&id).await?.is_some() {
+            return Err(HistoryError::ConversationExists(id).into());
+        }
+
+        // The session is reserved and locked in the same critical section, so that concurrent
+        // creations with the same ID can't both succeed, and turns wait until it is saved
+        let handle = Handle::new(session);
+        let guard = {
+            let mut sessions = self.inner.sessions.lock().unwrap();
+            let hash_map::Entry::Vacant(vacant) = sessions.entry(id.clone()) else {
+                return Err(HistoryError::ConversationExists(id).into());
+            };
+            let guard = Arc::clone(&handle.session)
+                .try_lock_owned()
+                .expect("a new session is unlocked");
+            vacant.insert(Entry {
+                handle: handle.clone(),
+                last_used: Instant::now(),
+            });
+            guard
+        };
+
+        if let Err(err) = self.inner.storage.save(&id, &guard).await {
+            let mut sessions = self.inner.sessions.lock().unwrap();
+            if sessions
+                .get(&id)
+                .is_some_and(|entry| Arc::ptr_eq(&entry.handle.session, &handle.session))
+            {
+                sessions.remove(&id);
+            }
+            handle.removed.store(true, Ordering::SeqCst);
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    /// Locks the session with the given ID, waiting for the current turn to finish, if any.
+    ///
+    /// Use this for turns that [`SessionStore::chat`] doesn't cover, such as streaming, and call
+    /// [`SessionStore::save`] once the history is updated.
+    pub async fn lock(&self, id: &str) -> crate::error::Result<OwnedMutexGuard<Session>> {
+        let (_, session) = self.lock_handle(id).await?;
+        Ok(session)
+    }
+
+    /// Saves a session locked with [`SessionStore::lock`] to the storage.
+    pub async fn save(&self, id: &str, session: &Session) -> crate::error::Result<()> {
+        Ok(self.inner.storage.save(id, session).await?)
+    }
+
+    /// Sends `message` in the session with the given ID, and stores the message and the
+    /// response in its history.
+    ///
+    /// If the session is removed while the response is generated, the response is returned
+    /// without being stored.
+    pub async fn chat(
+        &self,
+        id: &str,
+        message: ChatMessage,
+    ) -> crate::error::Result<ChatMessageResponse> {
+        let (handle, mut session) = self.lock_handle(id).await?;
+
+        let request = session.request(std::slice::from_ref(&message));
+        let response = self.inner.ollama.send_chat_messages(request).await?;
+
+        if handle.is_removed() {
+            return Ok(response);
+        }
+        session.history.push(message);
+        session.history.push(response.message.clone());
+        self.inner.storage.save(id, &session).await?;
+
+        Ok(response)
+    }
+
+    /// Removes the session with the given ID from memory and from the storage.
+    ///
+    /// A turn in progress in the session isn't stored.
+    pub async fn remove(&self, id: &str) -> crate::error::Result<()> {
+        if let Some(entry) = self.inner.sessions.lock().unwrap().remove(id) {
+            entry.handle.removed.store(true, Ordering::SeqCst);
+        }
+        Ok(self.inner.storage.delete(id).await?)
+    }
+
+    /// Returns the IDs of the sessions in memory.
+    pub fn session_ids(&self) -> Vec<String> {
+        self.inner
+            .sessions
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Evicts the sessions that weren't used for the TTL from memory, and returns how many were
+    /// evicted. Sessions that are in use are never evicted.
+    ///
+    /// This is done on every access to the store, but can also be called periodically to free
+    /// memory sooner.
+    pub fn evict_expired(&self) -> usize {
+        let Some(ttl) = self.inner.ttl else {
+            return 0;
+        };
+
+        let mut sessions = self.inner.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|_, entry| {
+            entry.last_used.elapsed() < ttl || Arc::strong_count(&entry.handle.session) > 1
+        });
+        before - sessions.len()
+    }
+
+    /// Locks the session with the given ID, failing if it is removed while waiting for the lock.
+    async fn lock_handle(
+        &self,
+        id: &str,
+    ) -> crate::error::Result<(Handle, OwnedMutexGuard<Session>)> {
+        let handle = self
+            .entry(id)
+            .await?
+            .ok_or_else(|| HistoryError::ConversationNotFound(id.to_string()))?;
+
+        let session = Arc::clone(&handle.session).lock_owned().await;
+        if handle.is_removed() {
+            return Err(HistoryError::ConversationNotFound(id.to_string()).into());
+        }
+        Ok((handle, session))
+    }
+
+    /// The session with the given ID, from memory or from the storage.
+    async fn entry(&self, id: &str) -> crate::error::Result<Option<Handle>> {
+        self.evict_expired();
+
+        if let Some(entry) = self.inner.sessions.lock().unwrap().get_mut(id) {
+            entry.last_used = Instant::now();
+            return Ok(Some(entry.handle.clone()));
+        }
+
+        let Some(session) = self.inner.storage.load(id).await? else {
+            return Ok(None);
+        };
+
+        // The session may have been loaded concurrently, in which case that one is kept
+        let mut sessions = self.inner.sessions.lock().unwrap();
+        let entry = sessions.entry(id.to_string()).or_insert_with(|| Entry {
+            handle: Handle::new(session),
+            last_used: Instant::now(),
+        });
+        Ok(Some(entry.handle.clone()))
+    }
+}
+
+/// Configures a [`SessionStore`] before it is built, and shared.
+///
+/// ```ignore
+/// let store = SessionStore::builder(ollama)
+///     .storage(storage)
+///     .ttl(Duration::from_secs(30 * 60))
+///     .build();
+/// ```
+pub struct SessionStoreBuilder<S: SessionStorage = NoStorage> {
+    ollama: Ollama,
+    storage: S,
+    ttl: Option<Duration>,
+}
+
+impl<S: SessionStorage> SessionStoreBuilder<S> {
+    /// Sets where the sessions are persisted. (Default: [`NoStorage`])
+    pub fn storage<T: SessionStorage>(self, storage: T) -> SessionStoreBuilder<T> {
+        SessionStoreBuilder {
+            ollama: self.ollama,
+            storage,
+            ttl: self.ttl,
+        }
+    }
+
+    /// Sets how long a session is kept in memory after its last use. (Default: forever)
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn build(self) -> SessionStore<S> {
+        SessionStore {
+            inner: Arc::new(Inner {
+                ollama: self.ollama,
+                storage: self.storage,
+                ttl: self.ttl,
+                sessions: Mutex::default(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, Response};
+
+    /// Answers chat requests with the number of messages received.
+    async fn serve() -> Ollama {
+        let (ollama, _) = test_util::serve(|request| {
+            let received = request.body["messages"].as_array().unwrap().len();
+            Response::json(serde_json::json!({
+                "model": request.body["model"],
+                "created_at": "2026-01-01T00:00:00Z",
+                "message": { "role": "assistant", "content": format!("received {received}") },
+                "done": true,
+            }))
+        })
+        .await;
+        ollama
+    }
+
+    #[derive(Default)]
+    struct MemoryStorage(Mutex<HashMap<String, Session>>);
+
+    impl SessionStorage for MemoryStorage {
+        async fn load(&self, id: &str) -> Result<Option<Session>, HistoryError> {
+            Ok(self.0.lock().unwrap().get(id).cloned())
+        }
+
+        async fn save(&self, id: &str, session: &Session) -> Result<(), HistoryError> {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(id.to_string(), session.clone());
+            Ok(())
+        }
+
+        async fn delete(&self, id: &str) -> Result<(), HistoryError> {
+            self.0.lock().unwrap().remove(id);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_turns_are_serialized() {
+        let store = SessionStore::new(serve().await);
+        let id = store
+            .create(Session::new("test".to_string()).system_prompt("Be brief."))
+            .await
+            .unwrap();
+
+        let (first, second) = tokio::join!(
+            store.chat(&id, ChatMessage::user("one".to_string())),
+            store.chat(&id, ChatMessage::user("two".to_string())),
+        );
+        first.unwrap();
+        second.unwrap();
+
+        let session = store.lock(&id).await.unwrap();
+        let contents = session
+            .history
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect::<Vec<_>>();
+        // The system prompt is sent, but not stored
+        assert_eq!(contents, ["one", "received 2", "two", "received 4"]);
+    }
+
+    #[tokio::test]
+    async fn expired_sessions_are_reloaded_from_storage() {
+        let store = SessionStore::builder(serve().await)
+            .storage(MemoryStorage::default())
+            .ttl(Duration::ZERO)
+            .build();
+        store
+            .create_with_id("session", Session::new("test".to_string()))
+            .await
+            .unwrap();
+        assert!(store
+            .create_with_id("session", Session::new("test".to_string()))
+            .await
+            .is_err());
+
+        store
+            .chat("session", ChatMessage::user("hi".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(store.evict_expired(), 1);
+        assert!(store.session_ids().is_empty());
+
+        assert_eq!(store.lock("session").await.unwrap().history.len(), 2);
+
+        store.remove("session").await.unwrap();
+        assert!(store.lock("session").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn concurrent_creations_with_the_same_id_conflict() {
+        let store = SessionStore::with_storage(serve().await, MemoryStorage::default());
+
+        let (first, second) = tokio::join!(
+            store.create_with_id("session", Session::new("first".to_string())),
+            store.create_with_id("session", Session::new("second".to_string())),
+        );
+        assert!(first.is_ok() != second.is_ok());
+        assert_eq!(store.session_ids(), ["session"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn removed_sessions_are_not_saved_by_turns_in_progress() {
+        let (started_sender, mut started) = tokio::sync::mpsc::unbounded_channel();
+        let (resume, resumed) = std::sync::mpsc::channel::<()>();
+        let resumed = Mutex::new(resumed);
+        let (ollama, _) = test_util::serve(move |request| {
+            // The response is held back until the session is removed
+            started_sender.send(()).unwrap();
+            resumed.lock().unwrap().recv().unwrap();
+            Response::json(serde_json::json!({
+                "model": request.body["model"],
+                "created_at": "2026-01-01T00:00:00Z",
+                "message": { "role": "assistant", "content": "hello" },
+                "done": true,
+            }))
+        })
+        .await;
+        let store = SessionStore::with_storage(ollama, MemoryStorage::default());
+        store
+            .create_with_id("session", Session::new("test".to_string()))
+            .await
+            .unwrap();
+
+        let turn = tokio::spawn({
+            let store = store.clone();
+            async move {
+                store
+                    .chat("session", ChatMessage::user("hi".to_string()))
+                    .await
+            }
+        });
+        started.recv().await.unwrap();
+        store.remove("session").await.unwrap();
+        resume.send(()).unwrap();
+
+        assert_eq!(turn.await.unwrap().unwrap().message.content, "hello");
+        assert!(store.inner.storage.0.lock().unwrap().is_empty());
+        assert!(store.lock("session").await.is_err());
+
+        // A turn waiting for the lock fails once the session is removed
+        store
+            .create_with_id("session", Session::new("test".to_string()))
+            .await
+            .unwrap();
+        let session = store.lock("session").await.unwrap();
+        let waiting = tokio::spawn({
+            let store = store.clone();
+            async move { store.lock("session").await.map(|_| ()) }
+        });
+        tokio::task::yield_now().await;
+        store.remove("session").await.unwrap();
+        drop(session);
+        assert!(waiting.await.unwrap().is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! A fake Ollama server for the unit tests.
+
+use std::collections::VecDeque;
+
+use serde_json::Value;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    sync::mpsc,
+};
+
+use crate::Ollama;
+
+/// A request received by the test server.
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    pub path: String,
+    /// The JSON body, or `Value::Null` for requests without a body.
+    pub body: Value,
+}
+
+/// A response of the test server.
+pub(crate) struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    /// A JSON response, ending with a newline so that streams can read it too.
+    pub fn json(body: Value) -> Self {
+        Self {
+            status: 200,
+            body: format!("{body}\n"),
+        }
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+/// Answers each request with `handler`, and sends the requests to the returned receiver.
+pub(crate) async fn serve(
+    mut handler: impl FnMut(&Request) -> Response + Send + 'static,
+) -> (Ollama, mpsc::UnboundedReceiver<Request>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind test server");
+    let addr = listener.local_addr().expect("test server addr");
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.expect("accept request");
+            let request = read_request(&mut socket).await;
+            let response = handler(&request);
+            // The test may not wait for the requests
+            let _ = sender.send(request);
+
+            let reason = if response.status == 200 {
+                "OK"
+            } else {
+                "Error"
+            };
+            let message = format!(
+                "HTTP/1.1 {} {reason}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                response.status,
+                response.body.len(),
+                response.body,
+            );
+            socket
+                .write_all(message.as_bytes())
+                .await
+                .expect("write response");
+        }
+    });
+
+    let ollama = Ollama::try_new(format!("http://{addr}")).expect("build test client");
+    (ollama, receiver)
+}
+
+/// Answers the requests with `responses`, in order.
+pub(crate) async fn serve_in_order(
+    responses: Vec<Value>,
+) -> (Ollama, mpsc::UnboundedReceiver<Request>) {
+    let mut responses = VecDeque::from(responses);
+    serve(move |_| Response::json(responses.pop_front().expect("no response left"))).await
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Request {
+    let mut request = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let read = socket.read(&mut buffer).await.expect("read request");
+        request.extend_from_slice(&buffer[..read]);
+
+        let text = String::from_utf8_lossy(&request);
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let length = head
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if body.len() >= length || read == 0 {
+                return Request {
+                    path: head.split(' ').nth(1).unwrap_or_default().to_string(),
+                    body: serde_json::from_str(body).unwrap_or(Value::Null),
+                };
+            }
+        }
+        assert!(read > 0, "connection closed before the end of the request");
+    }
+}
@@ -3,8 +3,5 @@
 async fn test_copy_model() {
     let ollama = ollama_rs::Ollama::default();
 
-    ollama
-        .copy_model("mario".into(), "mario_copy".into())
-        .await
-        .unwrap();
+    ollama.copy_model("mario", "mario_copy").await.unwrap();
 }
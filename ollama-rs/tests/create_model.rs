@@ -6,11 +6,11 @@ use tokio_stream::StreamExt;
 async fn test_create_model_stream() {
     let ollama = Ollama::default();
 
-    let request = CreateModelRequest::new("testmodel".into())
+    let request = CreateModelRequest::new("testmodel")
         .license("Test".into())
         .system("You're a chat bot. (very useful information)".into())
         .template("Template".into())
-        .from_model("llama2:latest".into());
+        .from_model("llama2:latest");
 
     let mut res = ollama.create_model_stream(request).await.unwrap();
 
@@ -35,11 +35,11 @@ async fn test_create_model_stream() {
 async fn test_create_model() {
     let ollama = Ollama::default();
 
-    let request = CreateModelRequest::new("testmodel".into())
+    let request = CreateModelRequest::new("testmodel")
         .license("Test".into())
         .system("You're a chat bot. (very useful information)".into())
         .template("Template".into())
-        .from_model("llama2:latest".into());
+        .from_model("llama2:latest");
 
     let res = ollama.create_model(request).await.unwrap();
 
@@ -1,5 +1,8 @@
 use ollama_rs::{
-    generation::completion::{request::GenerationRequest, GenerationResponseStream},
+    generation::{
+        completion::{request::GenerationRequest, GenerationResponseStream},
+        logprobs::LogprobsAnalysis,
+    },
     Ollama,
 };
 use tokio_stream::StreamExt;
@@ -51,6 +54,11 @@ async fn test_generation_with_logprobs() {
         );
     }
 
+    // The alternatives are limited to `top_logprobs`
+    assert!(logprobs.iter().all(|data| data.top_logprobs.len() <= 3));
+    assert_eq!(logprobs.text(), res.response);
+    assert!(logprobs.perplexity().unwrap() >= 1.0);
+
     println!("Non-streaming test passed. Response: {}", res.response);
     println!("Number of logprobs entries: {}", logprobs.len());
 }
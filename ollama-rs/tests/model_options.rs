@@ -34,20 +34,20 @@ fn model_options_json_data_driven() {
         (
             "extra fields flattened",
             ModelOptions::default()
-                .extra("penalize_newline", true)
+                .extra("custom_bool", true)
                 .extra("custom_float", 1.5),
             json!({
+                "custom_bool": true,
                 "custom_float": 1.5,
-                "penalize_newline": true,
             }),
         ),
         (
             "known options merged with extra",
             ModelOptions::default()
                 .top_k(40)
-                .extra("presence_penalty", 0.25),
+                .extra("custom_penalty", 0.25),
             json!({
-                "presence_penalty": 0.25,
+                "custom_penalty": 0.25,
                 "top_k": 40,
             }),
         ),
@@ -68,11 +68,11 @@ fn model_options_json_data_driven() {
             "unknown keys round-trip via extra",
             ModelOptions::default()
                 .seed(7)
-                .extra("penalize_newline", true)
+                .extra("custom_bool", true)
                 .extra("nested", json!({ "a": 1 })),
             json!({
+                "custom_bool": true,
                 "nested": { "a": 1 },
-                "penalize_newline": true,
                 "seed": 7,
             }),
         ),
@@ -80,3 +80,68 @@ fn model_options_json_data_driven() {
         assert_conversions(name, opts, value);
     }
 }
+
+#[test]
+fn runner_and_sampling_options_are_typed() {
+    let opts = ModelOptions::default()
+        .num_keep(-1)
+        .typical_p(0.75)
+        .presence_penalty(0.5)
+        .frequency_penalty(-0.5)
+        .penalize_newline(false)
+        .num_batch(256)
+        .main_gpu(1)
+        .use_mmap(false)
+        .use_mlock(true);
+    assert_conversions(
+        "typed runner and sampling options",
+        opts.clone(),
+        json!({
+            "num_keep": -1,
+            "typical_p": 0.75,
+            "presence_penalty": 0.5,
+            "frequency_penalty": -0.5,
+            "penalize_newline": false,
+            "num_batch": 256,
+            "main_gpu": 1,
+            "use_mmap": false,
+            "use_mlock": true,
+        }),
+    );
+
+    assert_eq!(opts.num_batch, Some(256));
+    assert_eq!(opts.use_mlock, Some(true));
+    assert_eq!(opts.temperature, None);
+    assert!(opts.validate().is_ok());
+}
+
+#[test]
+fn invalid_options_are_reported_by_field() {
+    let opts = ModelOptions::default()
+        .mirostat(3)
+        .top_p(1.5)
+        .temperature(f32::NAN)
+        .num_ctx(0)
+        .num_predict(-3)
+        .stop(vec!["".to_string()])
+        .extra("top_k", 40);
+
+    let fields = opts
+        .validate()
+        .unwrap_err()
+        .into_iter()
+        .map(|violation| violation.field)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        [
+            "mirostat",
+            "temperature",
+            "top_p",
+            "num_ctx",
+            "num_predict",
+            "stop",
+            "top_k"
+        ]
+    );
+}
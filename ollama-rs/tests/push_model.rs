@@ -10,7 +10,7 @@ async fn test_push_model() {
     let model_name = format!("{}/test_model:latest", env!("USER"));
 
     ollama
-        .copy_model("test_model".into(), model_name.clone())
+        .copy_model("test_model", model_name.clone())
         .await
         .unwrap();
 
@@ -1,5 +1,6 @@
 use base64::Engine;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use tokio::sync::Mutex;
 use tokio_stream::StreamExt;
 
 use ollama_rs::{
@@ -84,8 +85,8 @@ async fn test_send_chat_messages_with_history_stream() {
 
     assert!(done);
     // Should have user's message as well as AI's response
-    dbg!(&history.lock().unwrap());
-    assert_eq!(history.lock().unwrap().len(), 2);
+    dbg!(&history.lock().await);
+    assert_eq!(history.lock().await.len(), 2);
 }
 
 #[tokio::test]
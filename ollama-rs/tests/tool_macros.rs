@@ -0,0 +1,308 @@
+use std::sync::Arc;
+
+use ollama_rs::{
+    coordinator::Coordinator,
+    generation::tools::{Tool, ToolInfo},
+    Ollama,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Get the weather for a given city.
+///
+/// * city - City to get the weather for.
+/// * unit - Unit of the temperature.
+/// * days - Number of days to forecast.
+#[ollama_rs::function(name = "weather-forecast")]
+async fn get_weather(
+    city: String,
+    #[param(rename = "temperatureUnit", default = "celsius".to_string(), enum_values = ["celsius", "fahrenheit"])]
+    unit: String,
+    #[param(range(min = 1, max = 7), description = "How many days to forecast")] days: u8,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(format!("{city}: sunny for {days} days, 21 degrees {unit}"))
+}
+
+#[ollama_rs::function(description = "Get the CPU temperature in Celsius.")]
+async fn get_cpu_temperature() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    Ok("42.7".to_string())
+}
+
+#[derive(Serialize)]
+struct Forecast {
+    city: String,
+    temperatures: Vec<i32>,
+}
+
+/// Get the forecast for a given city.
+///
+/// * city - City to get the forecast for.
+#[ollama_rs::function]
+async fn get_forecast(city: String) -> Forecast {
+    Forecast {
+        city,
+        temperatures: vec![21, 23],
+    }
+}
+
+#[derive(Debug)]
+struct NotFound(String);
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} not found", self.0)
+    }
+}
+
+/// Look up the population of a given city.
+///
+/// * city - City to look up.
+#[ollama_rs::function]
+async fn get_population(city: String) -> Result<u64, NotFound> {
+    match city.as_str() {
+        "Paris" => Ok(2_102_650),
+        _ => Err(NotFound(city)),
+    }
+}
+
+/// Greet someone.
+///
+/// * name - Name of the person to greet.
+#[ollama_rs::function]
+async fn greet(name: String) -> std::io::Result<String> {
+    if name.is_empty() {
+        return Err(std::io::Error::other("nobody to greet"));
+    }
+    Ok(format!("Hello {name}"))
+}
+
+/// Count the words of a text.
+///
+/// * text - Text to count the words of.
+#[ollama_rs::function]
+fn count_words(text: String) -> usize {
+    std::thread::sleep(std::time::Duration::from_millis(1));
+    text.split_whitespace().count()
+}
+
+struct Counter {
+    step: u32,
+}
+
+#[ollama_rs::tools]
+impl Counter {
+    fn new(step: u32) -> Self {
+        Self { step }
+    }
+
+    /// Add the step to a number.
+    ///
+    /// * number - The number to add the step to.
+    #[tool(name = "add_step")]
+    async fn add(
+        &self,
+        #[param(range(min = 0))] number: u32,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok((number + self.step).to_string())
+    }
+
+    /// Reset a number to zero.
+    async fn reset(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok("0".to_string())
+    }
+}
+
+/// Convert a temperature from Celsius to Fahrenheit.
+#[derive(Deserialize, JsonSchema, Tool)]
+#[tool(name = "to-fahrenheit", handler = to_fahrenheit)]
+struct ToFahrenheit {
+    /// The temperature in Celsius.
+    celsius: f64,
+    #[schemars(description = "Whether to round the result.")]
+    #[serde(default)]
+    round: bool,
+}
+
+async fn to_fahrenheit(params: ToFahrenheit) -> Result<f64, std::convert::Infallible> {
+    let fahrenheit = params.celsius * 9.0 / 5.0 + 32.0;
+    Ok(if params.round {
+        fahrenheit.round()
+    } else {
+        fahrenheit
+    })
+}
+
+/// Get the UTC offset of a city, in hours.
+#[derive(Deserialize, JsonSchema, Tool)]
+#[tool(handler = utc_offset)]
+struct GetUTCOffset {
+    /// The city to get the offset of.
+    city: String,
+}
+
+async fn utc_offset(params: GetUTCOffset) -> i32 {
+    if params.city == "Paris" {
+        1
+    } else {
+        0
+    }
+}
+
+fn parameters(info: &ToolInfo) -> Value {
+    serde_json::to_value(&info.function.parameters).unwrap()
+}
+
+#[test]
+fn function_attributes_flow_into_tool_info() {
+    let info = ToolInfo::from_tool(&get_weather);
+    assert_eq!(info.function.name, "weather-forecast");
+    assert_eq!(
+        info.function.description,
+        "Get the weather for a given city."
+    );
+
+    let parameters = parameters(&info);
+    let properties = &parameters["properties"];
+    assert_eq!(
+        properties["city"]["description"],
+        "City to get the weather for."
+    );
+    assert!(properties.get("unit").is_none());
+    assert_eq!(
+        properties["temperatureUnit"]["enum"],
+        json!(["celsius", "fahrenheit"])
+    );
+    assert_eq!(properties["temperatureUnit"]["default"], "celsius");
+    assert_eq!(
+        properties["days"]["description"],
+        "How many days to forecast"
+    );
+    assert_eq!(properties["days"]["minimum"], 1);
+    assert_eq!(properties["days"]["maximum"], 7);
+    assert_eq!(parameters["required"], json!(["city", "days"]));
+
+    let info = ToolInfo::from_tool(&get_cpu_temperature);
+    assert_eq!(
+        info.function.description,
+        "Get the CPU temperature in Celsius."
+    );
+}
+
+#[tokio::test]
+async fn function_attributes_apply_to_arguments() {
+    let result = get_weather
+        .call(serde_json::from_value(json!({ "city": "Paris", "days": 2 })).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(result, "Paris: sunny for 2 days, 21 degrees celsius");
+
+    let result = get_weather
+        .call(
+            serde_json::from_value(
+                json!({ "city": "Paris", "days": 2, "temperatureUnit": "fahrenheit" }),
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result, "Paris: sunny for 2 days, 21 degrees fahrenheit");
+}
+
+#[tokio::test]
+async fn tools_impl_registers_methods() {
+    let counter = Arc::new(Counter::new(2));
+    let coordinator =
+        Coordinator::new(Ollama::default(), "model", vec![]).add_tool_group(Arc::clone(&counter));
+
+    let infos = coordinator.tool_infos();
+    let names = infos
+        .iter()
+        .map(|info| info.function.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["add_step", "reset"]);
+    assert_eq!(infos[0].function.description, "Add the step to a number.");
+    let parameters = parameters(&infos[0]);
+    assert_eq!(
+        parameters["properties"]["number"]["description"],
+        "The number to add the step to."
+    );
+    assert_eq!(parameters["properties"]["number"]["minimum"], 0);
+    assert_eq!(parameters["required"], json!(["number"]));
+
+    let mut add = __Counter_tools::add(counter);
+    let result = add
+        .call(serde_json::from_value(json!({ "number": 40 })).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(result, "42");
+}
+
+#[tokio::test]
+async fn function_outputs_are_converted() {
+    let result = get_forecast
+        .call(serde_json::from_value(json!({ "city": "Paris" })).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::from_str::<Value>(&result).unwrap(),
+        json!({ "city": "Paris", "temperatures": [21, 23] })
+    );
+
+    let result = get_population
+        .call(serde_json::from_value(json!({ "city": "Paris" })).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(result, "2102650");
+
+    let error = get_population
+        .call(serde_json::from_value(json!({ "city": "Atlantis" })).unwrap())
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Atlantis not found");
+
+    let error = greet
+        .call(serde_json::from_value(json!({ "name": "" })).unwrap())
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "nobody to greet");
+
+    let result = count_words
+        .call(serde_json::from_value(json!({ "text": "one two three" })).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(result, "3");
+}
+
+#[tokio::test]
+async fn derived_tool_calls_handler() {
+    let mut tool = ToFahrenheitTool;
+    let info = ToolInfo::from_tool(&tool);
+    assert_eq!(info.function.name, "to-fahrenheit");
+    assert_eq!(
+        info.function.description,
+        "Convert a temperature from Celsius to Fahrenheit."
+    );
+    assert_eq!(
+        parameters(&info)["properties"]["round"]["description"],
+        "Whether to round the result."
+    );
+
+    let result = tool
+        .call(serde_json::from_value(json!({ "celsius": 21.5, "round": true })).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(result, "71.0");
+}
+
+#[tokio::test]
+async fn derived_tool_outputs_are_converted() {
+    let mut tool = GetUTCOffsetTool;
+    assert_eq!(ToolInfo::from_tool(&tool).function.name, "get_utc_offset");
+
+    let result = tool
+        .call(serde_json::from_value(json!({ "city": "Paris" })).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(result, "1");
+}